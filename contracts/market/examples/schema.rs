@@ -4,8 +4,16 @@ use std::fs::create_dir_all;
 use cosmwasm_schema::{export_schema, remove_schemas, schema_for};
 
 use moneymarket::market::{
-    BorrowerInfoResponse, BorrowerInfosResponse, ConfigResponse, Cw20HookMsg, EpochStateResponse,
-    ExecuteMsg, InstantiateMsg, QueryMsg,
+    AterraToUnderlyingResponse, BorrowerHealthResponse, BorrowerInfoResponse,
+    BorrowerInfosResponse, BorrowerRateResponse, BorrowerUtilizationResponse,
+    ClaimableRewardResponse, ConfigAndStateResponse, ConfigAuditResponse, ConfigResponse,
+    Cw20HookMsg, EpochStateResponse, ExecuteMsg, FreshnessResponse, HistoricalStateResponse,
+    InstantiateMsg, IntegrityCheckResponse, IsLiquidatableResponse, LiabilityHistoryResponse,
+    LockedDepositResponse, MarketSummaryResponse, NetDepositRateResponse, PayoffAmountResponse,
+    PendingConfigChangesResponse, ProjectedReservesResponse, ProposalResponse, QueryMsg,
+    RateSpreadResponse, RatesResponse, ReferralVolumeResponse, RemainingDepositCapacityResponse,
+    ReserveTransferHistoryResponse, RewardIndexHistoryResponse, RewardStateResponse,
+    TaxAmountResponse, TotalDepositsResponse, TvlInReferenceResponse, UnderlyingToAterraResponse,
 };
 use moneymarket_market::state::State;
 
@@ -20,8 +28,38 @@ fn main() {
     export_schema(&schema_for!(Cw20HookMsg), &out_dir);
     export_schema(&schema_for!(QueryMsg), &out_dir);
     export_schema(&schema_for!(ConfigResponse), &out_dir);
+    export_schema(&schema_for!(ConfigAuditResponse), &out_dir);
     export_schema(&schema_for!(State), &out_dir);
     export_schema(&schema_for!(EpochStateResponse), &out_dir);
+    export_schema(&schema_for!(HistoricalStateResponse), &out_dir);
     export_schema(&schema_for!(BorrowerInfoResponse), &out_dir);
+    export_schema(&schema_for!(ClaimableRewardResponse), &out_dir);
     export_schema(&schema_for!(BorrowerInfosResponse), &out_dir);
+    export_schema(&schema_for!(BorrowerHealthResponse), &out_dir);
+    export_schema(&schema_for!(IsLiquidatableResponse), &out_dir);
+    export_schema(&schema_for!(BorrowerUtilizationResponse), &out_dir);
+    export_schema(&schema_for!(ConfigAndStateResponse), &out_dir);
+    export_schema(&schema_for!(ReferralVolumeResponse), &out_dir);
+    export_schema(&schema_for!(RewardIndexHistoryResponse), &out_dir);
+    export_schema(&schema_for!(LiabilityHistoryResponse), &out_dir);
+    export_schema(&schema_for!(ReserveTransferHistoryResponse), &out_dir);
+    export_schema(&schema_for!(TotalDepositsResponse), &out_dir);
+    export_schema(&schema_for!(RemainingDepositCapacityResponse), &out_dir);
+    export_schema(&schema_for!(RatesResponse), &out_dir);
+    export_schema(&schema_for!(BorrowerRateResponse), &out_dir);
+    export_schema(&schema_for!(TaxAmountResponse), &out_dir);
+    export_schema(&schema_for!(RewardStateResponse), &out_dir);
+    export_schema(&schema_for!(PendingConfigChangesResponse), &out_dir);
+    export_schema(&schema_for!(ProjectedReservesResponse), &out_dir);
+    export_schema(&schema_for!(RateSpreadResponse), &out_dir);
+    export_schema(&schema_for!(NetDepositRateResponse), &out_dir);
+    export_schema(&schema_for!(AterraToUnderlyingResponse), &out_dir);
+    export_schema(&schema_for!(UnderlyingToAterraResponse), &out_dir);
+    export_schema(&schema_for!(IntegrityCheckResponse), &out_dir);
+    export_schema(&schema_for!(TvlInReferenceResponse), &out_dir);
+    export_schema(&schema_for!(MarketSummaryResponse), &out_dir);
+    export_schema(&schema_for!(ProposalResponse), &out_dir);
+    export_schema(&schema_for!(FreshnessResponse), &out_dir);
+    export_schema(&schema_for!(LockedDepositResponse), &out_dir);
+    export_schema(&schema_for!(PayoffAmountResponse), &out_dir);
 }
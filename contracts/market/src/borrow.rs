@@ -1,44 +1,179 @@
 use anchor_token::distributor::ExecuteMsg as FaucetExecuteMsg;
 use cosmwasm_bignumber::{Decimal256, Uint256};
 use cosmwasm_std::{
-    attr, to_binary, Addr, BankMsg, Coin, CosmosMsg, Deps, DepsMut, Env, MessageInfo, Response,
-    StdResult, WasmMsg,
+    attr, to_binary, Addr, Binary, CosmosMsg, Deps, DepsMut, Env, Event, MessageInfo, ReplyOn,
+    Response, StdError, StdResult, SubMsg, WasmMsg,
+};
+use cw20::Cw20ExecuteMsg;
+use moneymarket::market::{
+    BorrowerHealthResponse, BorrowerInfoResponse, BorrowerInfosResponse,
+    BorrowerUtilizationResponse, ClaimableRewardResponse, IntegrityCheckResponse,
+    IsLiquidatableResponse, PayoffAmountResponse, RoundingMode,
 };
-use moneymarket::interest_model::BorrowRateResponse;
-use moneymarket::market::{BorrowerInfoResponse, BorrowerInfosResponse};
 use moneymarket::overseer::BorrowLimitResponse;
-use moneymarket::querier::{deduct_tax, query_balance, query_supply};
+use moneymarket::querier::query_token_balance;
 
-use crate::deposit::compute_exchange_rate_raw;
+use crate::deposit::{assert_redeem_lockup, compute_exchange_rate_raw};
 use crate::error::ContractError;
-use crate::querier::{query_borrow_limit, query_borrow_rate, query_target_deposit_rate};
+use crate::querier::{
+    cached_market_borrow_rate, query_borrow_limit, query_distributor_config,
+    query_market_borrow_rate, query_target_deposit_rate,
+};
+use crate::snapshot::MarketSnapshot;
+use crate::stable::{query_stable_balance, sender_tax, stable_funds, stable_transfer_msg};
 use crate::state::{
-    read_borrower_info, read_borrower_infos, read_config, read_state, store_borrower_info,
-    store_state, BorrowerInfo, Config, State,
+    read_all_borrower_infos, read_borrow_delegate, read_borrower_info, read_borrower_info_page,
+    read_borrower_infos, read_config, read_liquidation_callback, read_state,
+    remove_borrow_delegate, remove_liquidation_callback, store_borrow_delegate,
+    store_borrower_info, store_liquidation_callback, store_state, BorrowDelegate, BorrowerInfo,
+    Config, State,
 };
 
+pub const LIQUIDATION_CALLBACK_REPLY_ID: u64 = 2;
+
+/// Gas available to a liquidation callback, so that
+/// a borrower cannot block its own liquidation
+const LIQUIDATION_CALLBACK_GAS_LIMIT: u64 = 500_000;
+
+#[allow(clippy::too_many_arguments)]
 pub fn borrow_stable(
     deps: DepsMut,
     env: Env,
     info: MessageInfo,
     borrow_amount: Uint256,
     to: Option<Addr>,
+    fixed_rate: bool,
+    hook: Option<WasmMsg>,
+    on_behalf_of: Option<Addr>,
 ) -> Result<Response, ContractError> {
     let config: Config = read_config(deps.storage)?;
+    let borrower = on_behalf_of.clone().unwrap_or_else(|| info.sender.clone());
+    let recipient = to.unwrap_or_else(|| borrower.clone());
+
+    // A delegate can only send the borrowed stable to the borrower
+    if on_behalf_of.is_some() {
+        if recipient != borrower {
+            return Err(ContractError::InvalidRecipient(recipient.to_string()));
+        }
+        if hook.is_some() {
+            return Err(ContractError::InvalidBorrowHook {});
+        }
+    }
 
-    let mut state: State = read_state(deps.storage)?;
+    // Stable borrowed to the market itself or to aterra corrupts the balance accounting
+    let recipient_raw = deps.api.addr_canonicalize(recipient.as_str())?;
+    if recipient_raw == config.contract_addr || recipient_raw == config.aterra_contract {
+        return Err(ContractError::InvalidRecipient(recipient.to_string()));
+    }
+
+    if let Some(hook) = &hook {
+        assert_borrow_hook(deps.as_ref(), &config, &recipient, hook)?;
+    }
+
+    // Cannot borrow zero amount
+    if borrow_amount.is_zero() {
+        return Err(ContractError::ZeroBorrow(config.stable_denom));
+    }
+
+    if let Some(max_borrow_per_tx) = config.max_borrow_per_tx {
+        if borrow_amount > max_borrow_per_tx {
+            return Err(ContractError::BorrowPerTxExceeded {
+                requested: borrow_amount.into(),
+                max_borrow_per_tx: max_borrow_per_tx.into(),
+            });
+        }
+    }
 
-    let borrower = info.sender;
     let borrower_raw = deps.api.addr_canonicalize(borrower.as_str())?;
+
+    // Assert the sender is the borrower's delegate
+    let mut delegation = if on_behalf_of.is_some() {
+        let delegate_raw = deps.api.addr_canonicalize(info.sender.as_str())?;
+        Some(
+            read_borrow_delegate(deps.storage, &borrower_raw)
+                .filter(|delegation| delegation.delegate == delegate_raw)
+                .ok_or(ContractError::Unauthorized {})?,
+        )
+    } else {
+        None
+    };
+
+    let mut state: State = read_state(deps.storage)?;
     let mut liability: BorrowerInfo = read_borrower_info(deps.storage, &borrower_raw);
 
+    // Assert borrow cooldown
+    if config.borrow_cooldown > 0 && liability.last_borrowed > 0 {
+        let ready_at = liability.last_borrowed + config.borrow_cooldown;
+        if env.block.height < ready_at {
+            return Err(ContractError::BorrowCooldown { ready_at });
+        }
+    }
+
     // Compute interest
-    compute_interest(deps.as_ref(), &config, &mut state, env.block.height, None)?;
-    compute_borrower_interest(&state, &mut liability);
+    let interest_accrued =
+        compute_interest(deps.as_ref(), &config, &mut state, env.block.height, None)?;
+    settle_borrower_interest(&mut state, &mut liability, &config.rounding_mode);
 
     // Compute ANC reward
     compute_reward(&mut state, env.block.height);
-    compute_borrower_reward(&state, &mut liability);
+    compute_borrower_reward(&config, &state, &mut liability, env.block.height);
+
+    let current_balance = query_stable_balance(deps.as_ref(), &config)?;
+
+    // Only the stable not held as reserves, once interest has accrued to
+    // them, can be lent. Borrows beyond it fail unless partial fills are
+    // enabled; the limits below apply to the filled amount
+    let available = Decimal256::from_uint256(current_balance);
+    let available = if available > state.total_reserves {
+        (available - state.total_reserves) * Uint256::one()
+    } else {
+        Uint256::zero()
+    };
+
+    let requested_amount = borrow_amount;
+    if available.is_zero() || (borrow_amount > available && !config.partial_fill_borrows) {
+        return Err(ContractError::InsufficientLiquidity {
+            denom: config.stable_denom,
+            requested: borrow_amount.into(),
+        });
+    }
+
+    let borrow_amount = std::cmp::min(borrow_amount, available);
+
+    // Assert the delegated limit
+    if let Some(delegation) = &mut delegation {
+        if borrow_amount > delegation.limit {
+            return Err(ContractError::DelegatedBorrowExceeded {
+                requested: borrow_amount.into(),
+                remaining: delegation.limit.into(),
+            });
+        }
+
+        delegation.limit = delegation.limit - borrow_amount;
+    }
+
+    // Assert the per block limit, across all borrowers
+    if let Some(max_borrow_per_block) = config.max_borrow_per_block {
+        if state.borrowed_at_block != env.block.height {
+            state.borrowed_this_block = Uint256::zero();
+            state.borrowed_at_block = env.block.height;
+        }
+
+        if state.borrowed_this_block + borrow_amount > max_borrow_per_block {
+            let remaining = if max_borrow_per_block > state.borrowed_this_block {
+                max_borrow_per_block - state.borrowed_this_block
+            } else {
+                Uint256::zero()
+            };
+
+            return Err(ContractError::BorrowPerBlockExceeded {
+                requested: borrow_amount.into(),
+                remaining: remaining.into(),
+            });
+        }
+
+        state.borrowed_this_block += borrow_amount;
+    }
 
     let overseer = deps.api.addr_humanize(&config.overseer_contract)?;
     let borrow_limit_res: BorrowLimitResponse = query_borrow_limit(
@@ -49,41 +184,131 @@ pub fn borrow_stable(
     )?;
 
     if borrow_limit_res.borrow_limit < borrow_amount + liability.loan_amount {
-        return Err(ContractError::BorrowExceedsLimit(
-            borrow_limit_res.borrow_limit.into(),
-        ));
+        return Err(ContractError::BorrowExceedsLimit {
+            liability: (borrow_amount + liability.loan_amount).into(),
+            borrow_limit: borrow_limit_res.borrow_limit.into(),
+        });
     }
 
-    let current_balance = query_balance(
-        deps.as_ref(),
-        env.contract.address,
-        config.stable_denom.to_string(),
-    )?;
-
     // Assert borrow amount
     assert_max_borrow_factor(&config, &state, current_balance, borrow_amount)?;
+    assert_min_liquidity_ratio(&config, &state, current_balance, borrow_amount)?;
 
+    let mut locked_with_fallback = false;
+    if liability.loan_amount.is_zero() {
+        liability.opened_at = env.block.height;
+
+        // Newly opened positions start with the grace period
+        if config.borrow_grace_period > 0 {
+            liability.interest_free_until = env.block.height + config.borrow_grace_period;
+        }
+
+        // Lock the current borrow rate for fixed-rate positions
+        liability.fixed_rate = if fixed_rate {
+            let (borrow_rate, used_fallback) = query_market_borrow_rate(
+                deps.as_ref(),
+                &config,
+                current_balance,
+                state.total_liabilities + state.total_fixed_liabilities,
+                state.total_reserves,
+            )?;
+            locked_with_fallback = used_fallback;
+
+            Some(borrow_rate)
+        } else {
+            None
+        };
+    } else if fixed_rate != liability.fixed_rate.is_some() {
+        return Err(ContractError::FixedRateMismatch {});
+    }
+
+    if liability.loan_amount.is_zero() {
+        state.borrower_count += 1;
+    }
     liability.loan_amount += borrow_amount;
-    state.total_liabilities += Decimal256::from_uint256(borrow_amount);
+    liability.last_borrowed = env.block.height;
+    if let Some(fixed_rate) = liability.fixed_rate {
+        state.total_fixed_liabilities += Decimal256::from_uint256(borrow_amount);
+        state.fixed_interest_per_block += Decimal256::from_uint256(borrow_amount) * fixed_rate;
+    } else {
+        state.total_liabilities += Decimal256::from_uint256(borrow_amount);
+    }
+
     store_state(deps.storage, &state)?;
     store_borrower_info(deps.storage, &borrower_raw, &liability)?;
+    if let Some(delegation) = &delegation {
+        store_borrow_delegate(deps.storage, &borrower_raw, delegation)?;
+    }
+
+    let mut attributes = vec![
+        attr("action", "borrow_stable"),
+        attr("borrower", borrower.clone()),
+        attr("borrow_amount", borrow_amount),
+    ];
+    if delegation.is_some() {
+        attributes.push(attr("delegate", info.sender));
+    }
+    if borrow_amount != requested_amount {
+        attributes.push(attr("requested_amount", requested_amount));
+    }
+    if let Some(fixed_rate) = liability.fixed_rate {
+        attributes.push(attr("fixed_rate", fixed_rate.to_string()));
+    }
+    if locked_with_fallback {
+        attributes.push(attr("used_fallback", "true"));
+    }
 
     Ok(Response::new()
-        .add_message(CosmosMsg::Bank(BankMsg::Send {
-            to_address: to.unwrap_or_else(|| borrower.clone()).to_string(),
-            amount: vec![deduct_tax(
-                deps.as_ref(),
-                Coin {
-                    denom: config.stable_denom,
-                    amount: borrow_amount.into(),
-                },
-            )?],
-        }))
-        .add_attributes(vec![
-            attr("action", "borrow_stable"),
-            attr("borrower", borrower),
-            attr("borrow_amount", borrow_amount),
-        ]))
+        .add_events(interest_accrued)
+        .add_message(stable_transfer_msg(
+            deps.as_ref(),
+            &config,
+            recipient.to_string(),
+            borrow_amount,
+        )?)
+        .add_messages(hook)
+        .add_attributes(attributes))
+}
+
+/// A hook is sent by the market itself, so it must execute the borrow
+/// recipient without funds and never one of the contracts trusting the market
+fn assert_borrow_hook(
+    deps: Deps,
+    config: &Config,
+    recipient: &Addr,
+    hook: &WasmMsg,
+) -> Result<(), ContractError> {
+    let contract_addr = match hook {
+        WasmMsg::Execute {
+            contract_addr,
+            funds,
+            ..
+        } if funds.is_empty() => contract_addr,
+        _ => return Err(ContractError::InvalidBorrowHook {}),
+    };
+
+    if contract_addr != recipient.as_str()
+        || (config.stable_cw20 && *contract_addr == config.stable_denom)
+    {
+        return Err(ContractError::InvalidBorrowHook {});
+    }
+
+    let contract_raw = deps.api.addr_canonicalize(contract_addr)?;
+    if [
+        &config.contract_addr,
+        &config.aterra_contract,
+        &config.interest_model,
+        &config.distribution_model,
+        &config.overseer_contract,
+        &config.collector_contract,
+        &config.distributor_contract,
+    ]
+    .contains(&&contract_raw)
+    {
+        return Err(ContractError::InvalidBorrowHook {});
+    }
+
+    Ok(())
 }
 
 pub fn repay_stable_from_liquidation(
@@ -98,34 +323,201 @@ pub fn repay_stable_from_liquidation(
         return Err(ContractError::Unauthorized {});
     }
 
-    let cur_balance: Uint256 = query_balance(
-        deps.as_ref(),
-        env.contract.address.clone(),
-        config.stable_denom.to_string(),
-    )?;
+    let cur_balance: Uint256 = query_stable_balance(deps.as_ref(), &config)?;
+
+    let callback = read_liquidation_callback(
+        deps.storage,
+        &deps.api.addr_canonicalize(borrower.as_str())?,
+    );
 
-    // override env
-    let mut info = info;
+    let recovered_amount = cur_balance - prev_balance;
+    let liquidation_fee = recovered_amount * config.liquidation_fee_rate;
+    let res = if liquidation_fee.is_zero() {
+        repay(deps, env, borrower.clone(), recovered_amount)?
+    } else {
+        // Accrue up to the recovery before moving the fee into reserves,
+        // so the repay below computes no further interest
+        let mut state: State = read_state(deps.storage)?;
+        let interest_accrued = compute_interest(
+            deps.as_ref(),
+            &config,
+            &mut state,
+            env.block.height,
+            Some(recovered_amount),
+        )?;
+        state.total_reserves += Decimal256::from_uint256(liquidation_fee);
+        store_state(deps.storage, &state)?;
+
+        repay(
+            deps,
+            env,
+            borrower.clone(),
+            recovered_amount - liquidation_fee,
+        )?
+        .add_events(interest_accrued)
+        .add_attribute("liquidation_fee", liquidation_fee)
+    };
 
-    info.sender = borrower;
-    info.funds = vec![Coin {
-        denom: config.stable_denom,
-        amount: (cur_balance - prev_balance).into(),
-    }];
+    if let Some(msg) = callback {
+        return Ok(res.add_submessage(SubMsg {
+            id: LIQUIDATION_CALLBACK_REPLY_ID,
+            msg: CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: borrower.to_string(),
+                funds: vec![],
+                msg,
+            }),
+            gas_limit: Some(LIQUIDATION_CALLBACK_GAS_LIMIT),
+            reply_on: ReplyOn::Error,
+        }));
+    }
 
-    repay_stable(deps, env, info)
+    Ok(res)
+}
+
+pub fn set_liquidation_callback(
+    deps: DepsMut,
+    info: MessageInfo,
+    msg: Option<Binary>,
+) -> Result<Response, ContractError> {
+    let borrower_raw = deps.api.addr_canonicalize(info.sender.as_str())?;
+    if let Some(msg) = msg {
+        store_liquidation_callback(deps.storage, &borrower_raw, &msg)?;
+    } else {
+        remove_liquidation_callback(deps.storage, &borrower_raw);
+    }
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "set_liquidation_callback"),
+        attr("borrower", info.sender),
+    ]))
+}
+
+pub fn set_borrow_delegate(
+    deps: DepsMut,
+    info: MessageInfo,
+    delegate: Option<Addr>,
+    limit: Uint256,
+) -> Result<Response, ContractError> {
+    let borrower_raw = deps.api.addr_canonicalize(info.sender.as_str())?;
+    if let Some(delegate) = &delegate {
+        store_borrow_delegate(
+            deps.storage,
+            &borrower_raw,
+            &BorrowDelegate {
+                delegate: deps.api.addr_canonicalize(delegate.as_str())?,
+                limit,
+            },
+        )?;
+    } else {
+        remove_borrow_delegate(deps.storage, &borrower_raw);
+    }
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "set_borrow_delegate"),
+        attr("borrower", info.sender),
+        attr(
+            "delegate",
+            delegate.map_or_else(String::new, |delegate| delegate.to_string()),
+        ),
+        attr("limit", limit),
+    ]))
 }
 
-pub fn repay_stable(deps: DepsMut, env: Env, info: MessageInfo) -> Result<Response, ContractError> {
+/// Repay with the attached stable plus the underlying of `aterra_amount`,
+/// burnt from the sender's allowance to the market. The underlying
+/// already sits in the market, so it is applied to the loan in place;
+/// only the aterra covering the loan left after the stable is burnt
+pub fn repay_stable(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    aterra_amount: Option<Uint256>,
+) -> Result<Response, ContractError> {
     let config: Config = read_config(deps.storage)?;
 
     // Check stable denom deposit
-    let amount: Uint256 = info
-        .funds
-        .iter()
-        .find(|c| c.denom == config.stable_denom)
-        .map(|c| Uint256::from(c.amount))
-        .unwrap_or_else(Uint256::zero);
+    let amount: Uint256 = stable_funds(&config, &info)?;
+
+    let aterra_amount = aterra_amount.unwrap_or_else(Uint256::zero);
+    if aterra_amount.is_zero() {
+        return repay(deps, env, info.sender, amount);
+    }
+
+    assert_redeem_lockup(deps.as_ref(), &config, &env, &info.sender)?;
+
+    // The attached stable is not part of the exchange rate yet
+    let mut state: State = read_state(deps.storage)?;
+    let snapshot = MarketSnapshot::load(deps.as_ref(), &config, Some(amount))?;
+    let interest_accrued = compute_interest_with_snapshot(
+        deps.as_ref(),
+        &config,
+        &mut state,
+        env.block.height,
+        &snapshot,
+    )?;
+    let exchange_rate = snapshot.exchange_rate(&state);
+
+    // Stable is only refunded through redemption, so the aterra side never
+    // overpays; the fraction of a unit rounding up leaves stays in the market
+    let mut liability: BorrowerInfo = read_borrower_info(
+        deps.storage,
+        &deps.api.addr_canonicalize(info.sender.as_str())?,
+    );
+    // repay settles the position itself
+    settle_borrower_interest(&mut state.clone(), &mut liability, &config.rounding_mode);
+    let loan_left = if liability.loan_amount > amount {
+        liability.loan_amount - amount
+    } else {
+        Uint256::zero()
+    };
+
+    let mut aterra_needed = loan_left / exchange_rate;
+    if aterra_needed * exchange_rate < loan_left {
+        aterra_needed += Uint256::one();
+    }
+
+    let aterra_amount = std::cmp::min(aterra_amount, aterra_needed);
+    if aterra_amount.is_zero() {
+        return repay(deps, env, info.sender, amount);
+    }
+
+    let aterra_repay_amount = std::cmp::min(aterra_amount * exchange_rate, loan_left);
+
+    state.prev_aterra_supply = state.prev_aterra_supply - aterra_amount;
+    store_state(deps.storage, &state)?;
+
+    let burn_msg = CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: deps.api.addr_humanize(&config.aterra_contract)?.to_string(),
+        funds: vec![],
+        msg: to_binary(&Cw20ExecuteMsg::BurnFrom {
+            owner: info.sender.to_string(),
+            amount: aterra_amount.into(),
+        })?,
+    });
+
+    let res = repay(deps, env, info.sender, amount + aterra_repay_amount)?;
+    Ok(Response::new()
+        .add_events(interest_accrued)
+        .add_events(res.events)
+        .add_message(burn_msg)
+        .add_submessages(res.messages)
+        .add_attributes(res.attributes)
+        .add_attributes(vec![
+            attr("aterra_burn_amount", aterra_amount),
+            attr("aterra_repay_amount", aterra_repay_amount),
+        ]))
+}
+
+/// Repay `amount` of stable already received by the contract;
+/// an overpayment clears the loan and the excess is refunded
+/// to the borrower rather than rejected
+pub(crate) fn repay(
+    deps: DepsMut,
+    env: Env,
+    borrower: Addr,
+    amount: Uint256,
+) -> Result<Response, ContractError> {
+    let config: Config = read_config(deps.storage)?;
 
     // Cannot deposit zero amount
     if amount.is_zero() {
@@ -134,56 +526,73 @@ pub fn repay_stable(deps: DepsMut, env: Env, info: MessageInfo) -> Result<Respon
 
     let mut state: State = read_state(deps.storage)?;
 
-    let borrower = info.sender;
     let borrower_raw = deps.api.addr_canonicalize(borrower.as_str())?;
     let mut liability: BorrowerInfo = read_borrower_info(deps.storage, &borrower_raw);
 
     // Compute interest
-    compute_interest(
+    let interest_accrued = compute_interest(
         deps.as_ref(),
         &config,
         &mut state,
         env.block.height,
         Some(amount),
     )?;
-    compute_borrower_interest(&state, &mut liability);
+    settle_borrower_interest(&mut state, &mut liability, &config.rounding_mode);
 
     // Compute ANC reward
     compute_reward(&mut state, env.block.height);
-    compute_borrower_reward(&state, &mut liability);
+    compute_borrower_reward(&config, &state, &mut liability, env.block.height);
 
     let repay_amount: Uint256;
     let mut messages: Vec<CosmosMsg> = vec![];
     if liability.loan_amount < amount {
+        // Overpayment clamps the loan to zero
         repay_amount = liability.loan_amount;
         liability.loan_amount = Uint256::zero();
 
         // Payback left repay amount to sender
-        messages.push(CosmosMsg::Bank(BankMsg::Send {
-            to_address: borrower.to_string(),
-            amount: vec![deduct_tax(
-                deps.as_ref(),
-                Coin {
-                    denom: config.stable_denom,
-                    amount: (amount - repay_amount).into(),
-                },
-            )?],
-        }));
+        messages.push(stable_transfer_msg(
+            deps.as_ref(),
+            &config,
+            borrower.to_string(),
+            amount - repay_amount,
+        )?);
     } else {
         repay_amount = amount;
-        liability.loan_amount = liability.loan_amount - repay_amount;
+        liability.loan_amount = liability.loan_amount - amount;
+    }
+
+    if let Some(fixed_rate) = liability.fixed_rate {
+        state.total_fixed_liabilities =
+            state.total_fixed_liabilities - Decimal256::from_uint256(repay_amount);
+        remove_fixed_interest(
+            &mut state,
+            Decimal256::from_uint256(repay_amount) * fixed_rate,
+        );
+
+        // Closed positions may reopen with either rate mode
+        if liability.loan_amount.is_zero() {
+            liability.fixed_rate = None;
+        }
+    } else {
+        state.total_liabilities = state.total_liabilities - Decimal256::from_uint256(repay_amount);
     }
 
-    state.total_liabilities = state.total_liabilities - Decimal256::from_uint256(repay_amount);
+    if liability.loan_amount.is_zero() && !repay_amount.is_zero() {
+        state.borrower_count = state.borrower_count.saturating_sub(1);
+    }
 
     store_borrower_info(deps.storage, &borrower_raw, &liability)?;
     store_state(deps.storage, &state)?;
 
-    Ok(Response::new().add_messages(messages).add_attributes(vec![
-        attr("action", "repay_stable"),
-        attr("borrower", borrower),
-        attr("repay_amount", repay_amount),
-    ]))
+    Ok(Response::new()
+        .add_events(interest_accrued)
+        .add_messages(messages)
+        .add_attributes(vec![
+            attr("action", "repay_stable"),
+            attr("borrower", borrower),
+            attr("repay_amount", repay_amount),
+        ]))
 }
 
 pub fn claim_rewards(
@@ -193,6 +602,10 @@ pub fn claim_rewards(
     to: Option<Addr>,
 ) -> Result<Response, ContractError> {
     let config: Config = read_config(deps.storage)?;
+    if config.claims_paused {
+        return Err(ContractError::ClaimsPaused {});
+    }
+
     let mut state: State = read_state(deps.storage)?;
 
     let borrower = info.sender;
@@ -200,13 +613,16 @@ pub fn claim_rewards(
     let mut liability: BorrowerInfo = read_borrower_info(deps.storage, &borrower_raw);
 
     // Compute interest
-    compute_interest(deps.as_ref(), &config, &mut state, env.block.height, None)?;
-    compute_borrower_interest(&state, &mut liability);
+    let interest_accrued =
+        compute_interest(deps.as_ref(), &config, &mut state, env.block.height, None)?;
+    settle_borrower_interest(&mut state, &mut liability, &config.rounding_mode);
 
     // Compute ANC reward
     compute_reward(&mut state, env.block.height);
-    compute_borrower_reward(&state, &mut liability);
+    compute_borrower_reward(&config, &state, &mut liability, env.block.height);
 
+    // Only whole units can be spent from the distributor, so round the
+    // claimable amount down and carry the fractional remainder forward
     let claim_amount = liability.pending_rewards * Uint256::one();
     liability.pending_rewards = liability.pending_rewards - Decimal256::from_uint256(claim_amount);
 
@@ -233,10 +649,13 @@ pub fn claim_rewards(
         vec![]
     };
 
-    Ok(Response::new().add_messages(messages).add_attributes(vec![
-        attr("action", "claim_rewards"),
-        attr("claim_amount", claim_amount),
-    ]))
+    Ok(Response::new()
+        .add_events(interest_accrued)
+        .add_messages(messages)
+        .add_attributes(vec![
+            attr("action", "claim_rewards"),
+            attr("claim_amount", claim_amount),
+        ]))
 }
 
 /// Compute interest and update state
@@ -247,39 +666,52 @@ pub fn compute_interest(
     state: &mut State,
     block_height: u64,
     deposit_amount: Option<Uint256>,
-) -> StdResult<()> {
+) -> StdResult<Option<Event>> {
     if state.last_interest_updated >= block_height {
-        return Ok(());
+        return Ok(None);
     }
 
-    let aterra_supply = query_supply(deps, deps.api.addr_humanize(&config.aterra_contract)?)?;
-    let balance: Uint256 = query_balance(
-        deps,
-        deps.api.addr_humanize(&config.contract_addr)?,
-        config.stable_denom.to_string(),
-    )? - deposit_amount.unwrap_or_else(Uint256::zero);
+    let snapshot = MarketSnapshot::load(deps, config, deposit_amount)?;
+    compute_interest_with_snapshot(deps, config, state, block_height, &snapshot)
+}
 
-    let borrow_rate_res: BorrowRateResponse = query_borrow_rate(
-        deps,
-        deps.api.addr_humanize(&config.interest_model)?,
-        balance,
-        state.total_liabilities,
-        state.total_reserves,
-    )?;
+/// compute_interest with the balances of an already loaded snapshot
+pub(crate) fn compute_interest_with_snapshot(
+    deps: Deps,
+    config: &Config,
+    state: &mut State,
+    block_height: u64,
+    snapshot: &MarketSnapshot,
+) -> StdResult<Option<Event>> {
+    if state.last_interest_updated >= block_height {
+        return Ok(None);
+    }
+
+    let (borrow_rate, used_fallback) =
+        cached_market_borrow_rate(deps, config, state, block_height, snapshot.balance)?;
 
     let target_deposit_rate: Decimal256 =
         query_target_deposit_rate(deps, deps.api.addr_humanize(&config.overseer_contract)?)?;
 
-    compute_interest_raw(
+    let interest_accrued = compute_interest_raw(
         state,
         block_height,
-        balance,
-        aterra_supply,
-        borrow_rate_res.rate,
+        snapshot.balance,
+        snapshot.aterra_supply,
+        borrow_rate,
         target_deposit_rate,
+        &config.rounding_mode,
     );
 
-    Ok(())
+    Ok(mark_fallback(interest_accrued, used_fallback))
+}
+
+/// Flag an interest_accrued event computed with a fallback borrow rate
+pub(crate) fn mark_fallback(interest_accrued: Option<Event>, used_fallback: bool) -> Option<Event> {
+    match interest_accrued {
+        Some(event) if used_fallback => Some(event.add_attribute("used_fallback", "true")),
+        interest_accrued => interest_accrued,
+    }
 }
 
 // CONTRACT: to use this function as state update purpose,
@@ -287,6 +719,9 @@ pub fn compute_interest(
 // * state.prev_aterra_supply
 // * state.prev_exchange_rate
 // * state.last_interest_updated
+//
+// Returns an `interest_accrued` event for the executor to emit,
+// or None when no block has passed since the last update
 pub fn compute_interest_raw(
     state: &mut State,
     block_height: u64,
@@ -294,19 +729,30 @@ pub fn compute_interest_raw(
     aterra_supply: Uint256,
     borrow_rate: Decimal256,
     target_deposit_rate: Decimal256,
-) {
+    rounding_mode: &RoundingMode,
+) -> Option<Event> {
+    // a last_interest_updated ahead of the block accrues nothing
+    // rather than underflowing the elapsed blocks
     if state.last_interest_updated >= block_height {
-        return;
+        return None;
     }
 
-    let passed_blocks = Decimal256::from_uint256(block_height - state.last_interest_updated);
+    let elapsed_blocks = block_height - state.last_interest_updated;
+    let passed_blocks = Decimal256::from_uint256(elapsed_blocks);
 
+    // Whole block counts multiply exactly; only the growth
+    // of liabilities and the index needs rounding
     let interest_factor = passed_blocks * borrow_rate;
-    let interest_accrued = state.total_liabilities * interest_factor;
+    let interest_accrued = mul_rounded(state.total_liabilities, interest_factor, rounding_mode);
+    let fixed_interest_accrued = state.fixed_interest_per_block * passed_blocks;
 
-    state.global_interest_index =
-        state.global_interest_index * (Decimal256::one() + interest_factor);
+    state.global_interest_index = mul_rounded(
+        state.global_interest_index,
+        Decimal256::one() + interest_factor,
+        rounding_mode,
+    );
     state.total_liabilities += interest_accrued;
+    state.total_fixed_liabilities += fixed_interest_accrued;
 
     let mut exchange_rate = compute_exchange_rate_raw(state, aterra_supply, balance);
     let effective_deposit_rate = exchange_rate / state.prev_exchange_rate;
@@ -328,16 +774,239 @@ pub fn compute_interest_raw(
     state.prev_aterra_supply = aterra_supply;
     state.prev_exchange_rate = exchange_rate;
     state.last_interest_updated = block_height;
+
+    let utilization = compute_utilization(state, balance);
+    if utilization > state.peak_utilization {
+        state.peak_utilization = utilization;
+    }
+
+    Some(Event::new("interest_accrued").add_attributes(vec![
+        attr("rate", borrow_rate.to_string()),
+        attr("elapsed_blocks", elapsed_blocks.to_string()),
+        attr(
+            "liability_delta",
+            (interest_accrued + fixed_interest_accrued).to_string(),
+        ),
+        attr("new_index", state.global_interest_index.to_string()),
+    ]))
+}
+
+/// utilization = liabilities / deposits, with
+/// deposits = balance + liabilities - reserves
+pub(crate) fn compute_utilization(state: &State, balance: Uint256) -> Decimal256 {
+    let liabilities = state.total_liabilities + state.total_fixed_liabilities;
+    let assets = Decimal256::from_uint256(balance) + liabilities;
+    if assets <= state.total_reserves {
+        return Decimal256::zero();
+    }
+
+    liabilities / (assets - state.total_reserves)
+}
+
+/// Maintenance for after a migration changes how interest accrues;
+/// settles each stored borrower as if it had just interacted
+pub fn resync_borrowers(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    start_after: Option<Addr>,
+    limit: Option<u32>,
+) -> Result<Response, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+
+    // permission check
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner_addr {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let mut state: State = read_state(deps.storage)?;
+    let interest_accrued =
+        compute_interest(deps.as_ref(), &config, &mut state, env.block.height, None)?;
+    compute_reward(&mut state, env.block.height);
+
+    let start_after = start_after
+        .map(|start_after| deps.api.addr_canonicalize(start_after.as_str()))
+        .transpose()?;
+    let borrowers = read_borrower_info_page(
+        deps.storage,
+        start_after,
+        limit,
+        config.max_borrower_infos_limit,
+    )?;
+
+    let resynced = borrowers.len();
+    let mut last_borrower = None;
+    for (borrower_raw, mut liability) in borrowers {
+        settle_borrower_interest(&mut state, &mut liability, &config.rounding_mode);
+        compute_borrower_reward(&config, &state, &mut liability, env.block.height);
+        store_borrower_info(deps.storage, &borrower_raw, &liability)?;
+        last_borrower = Some(borrower_raw);
+    }
+
+    store_state(deps.storage, &state)?;
+
+    let mut attributes = vec![
+        attr("action", "resync_borrowers"),
+        attr("resynced", resynced.to_string()),
+    ];
+    if let Some(last_borrower) = last_borrower {
+        attributes.push(attr(
+            "last_borrower",
+            deps.api.addr_humanize(&last_borrower)?,
+        ));
+    }
+
+    Ok(Response::new()
+        .add_events(interest_accrued)
+        .add_attributes(attributes))
+}
+
+/// `a * b`, rounded at the decimal precision by `rounding_mode`;
+/// Floor matches the plain Decimal256 product
+pub(crate) fn mul_rounded(
+    a: Decimal256,
+    b: Decimal256,
+    rounding_mode: &RoundingMode,
+) -> Decimal256 {
+    let product = a.0 * b.0;
+    let remainder = product % Decimal256::DECIMAL_FRACTIONAL;
+    let floor = Decimal256(product / Decimal256::DECIMAL_FRACTIONAL);
+
+    let round_up = match rounding_mode {
+        RoundingMode::Floor => false,
+        RoundingMode::Ceil => !remainder.is_zero(),
+        RoundingMode::Nearest => remainder + remainder >= Decimal256::DECIMAL_FRACTIONAL,
+    };
+
+    if round_up {
+        floor + Decimal256::from_ratio(1u64, Decimal256::DECIMAL_FRACTIONAL)
+    } else {
+        floor
+    }
+}
+
+/// Compute new interest and apply to liability, rounding the
+/// loan to a whole unit by `rounding_mode`
+pub(crate) fn compute_borrower_interest(
+    state: &State,
+    liability: &mut BorrowerInfo,
+    rounding_mode: &RoundingMode,
+) {
+    let product = liability.loan_amount.0 * state.global_interest_index.0;
+    let remainder = product % liability.interest_index.0;
+    let floor = Uint256(product / liability.interest_index.0);
+
+    let round_up = match rounding_mode {
+        RoundingMode::Floor => false,
+        RoundingMode::Ceil => !remainder.is_zero(),
+        RoundingMode::Nearest => remainder + remainder >= liability.interest_index.0,
+    };
+
+    liability.loan_amount = if round_up {
+        floor + Uint256::one()
+    } else {
+        floor
+    };
+    liability.interest_index = state.global_interest_index;
 }
 
-/// Compute new interest and apply to liability
-pub(crate) fn compute_borrower_interest(state: &State, liability: &mut BorrowerInfo) {
-    liability.loan_amount =
-        liability.loan_amount * state.global_interest_index / liability.interest_index;
+/// Compute new interest at the locked rate and apply to a fixed-rate liability
+/// Interest is simple between settlements, matching the pool-wide
+/// `fixed_interest_per_block` accrual in compute_interest_raw
+pub(crate) fn compute_fixed_borrower_interest(
+    state: &State,
+    liability: &mut BorrowerInfo,
+    fixed_rate: Decimal256,
+) {
+    if state.last_interest_updated > liability.last_interest_updated {
+        let passed_blocks =
+            Decimal256::from_uint256(state.last_interest_updated - liability.last_interest_updated);
+        liability.loan_amount += liability.loan_amount * (passed_blocks * fixed_rate);
+    }
+
     liability.interest_index = state.global_interest_index;
 }
 
+/// Compute new interest and apply to liability, waiving the
+/// interest accrued within the grace period of a new position.
+/// The waived amount is removed from total liabilities here,
+/// when the position is settled
+pub(crate) fn settle_borrower_interest(
+    state: &mut State,
+    liability: &mut BorrowerInfo,
+    rounding_mode: &RoundingMode,
+) {
+    let prev_loan_amount = liability.loan_amount;
+    if let Some(fixed_rate) = liability.fixed_rate {
+        compute_fixed_borrower_interest(state, liability, fixed_rate);
+    } else {
+        compute_borrower_interest(state, liability, rounding_mode);
+    }
+
+    let block_height = state.last_interest_updated;
+    let last_interest_updated = liability.last_interest_updated;
+    liability.last_interest_updated = block_height;
+
+    if let Some(fixed_rate) = liability.fixed_rate {
+        settle_fixed_interest(state, liability, prev_loan_amount, fixed_rate);
+    }
+
+    if liability.interest_free_until <= last_interest_updated
+        || block_height <= last_interest_updated
+    {
+        return;
+    }
+
+    // Waive the share of the accrual that falls in the grace period
+    let accrued = liability.loan_amount - prev_loan_amount;
+    let waived_amount = if block_height <= liability.interest_free_until {
+        accrued
+    } else {
+        accrued
+            * Decimal256::from_ratio(
+                liability.interest_free_until - last_interest_updated,
+                block_height - last_interest_updated,
+            )
+    };
+
+    liability.loan_amount = liability.loan_amount - waived_amount;
+    if let Some(fixed_rate) = liability.fixed_rate {
+        state.total_fixed_liabilities =
+            state.total_fixed_liabilities - Decimal256::from_uint256(waived_amount);
+        remove_fixed_interest(state, Decimal256::from_uint256(waived_amount) * fixed_rate);
+    } else {
+        state.total_liabilities = state.total_liabilities - Decimal256::from_uint256(waived_amount);
+    }
+}
+
+/// Move the borrower's share of the pool-wide fixed accrual
+/// from the previous loan amount to the settled one
+fn settle_fixed_interest(
+    state: &mut State,
+    liability: &BorrowerInfo,
+    prev_loan_amount: Uint256,
+    fixed_rate: Decimal256,
+) {
+    remove_fixed_interest(
+        state,
+        Decimal256::from_uint256(prev_loan_amount) * fixed_rate,
+    );
+    state.fixed_interest_per_block += Decimal256::from_uint256(liability.loan_amount) * fixed_rate;
+}
+
+/// Per position products may round differently from the running sum,
+/// so the removal saturates at zero
+fn remove_fixed_interest(state: &mut State, interest_per_block: Decimal256) {
+    state.fixed_interest_per_block = if state.fixed_interest_per_block > interest_per_block {
+        state.fixed_interest_per_block - interest_per_block
+    } else {
+        Decimal256::zero()
+    };
+}
+
 /// Compute distributed reward and update global index
+/// The index increment is rounded down, so the sum of all
+/// borrower rewards never exceeds the accrued amount
 pub fn compute_reward(state: &mut State, block_height: u64) {
     if state.last_reward_updated >= block_height {
         return;
@@ -355,11 +1024,78 @@ pub fn compute_reward(state: &mut State, block_height: u64) {
 }
 
 /// Compute reward amount a borrower received
-pub(crate) fn compute_borrower_reward(state: &State, liability: &mut BorrowerInfo) {
-    liability.pending_rewards += Decimal256::from_uint256(liability.loan_amount)
-        / state.global_interest_index
-        * (state.global_reward_index - liability.reward_index);
+/// Multiply before dividing to keep precision; every step
+/// rounds down in favor of the reward pool.
+/// Fixed-rate positions sit outside the variable pool the
+/// reward index is distributed over, so they earn no rewards.
+/// Tenure tiers scale each position's share of the emission
+/// relative to the top tier, so boosted claims never exceed it
+pub(crate) fn compute_borrower_reward(
+    config: &Config,
+    state: &State,
+    liability: &mut BorrowerInfo,
+    block_height: u64,
+) {
+    if liability.fixed_rate.is_none() {
+        liability.pending_rewards += Decimal256::from_uint256(liability.loan_amount)
+            * (state.global_reward_index - liability.reward_index)
+            * reward_tenure_weight(config, liability, block_height)
+            / state.global_interest_index;
+    }
     liability.reward_index = state.global_reward_index;
+    liability.reward_updated_at = block_height;
+}
+
+/// Multiplier of the highest tier the position's tenure reaches;
+/// positions opened before `opened_at` was recorded count from genesis
+pub(crate) fn reward_tenure_multiplier(
+    config: &Config,
+    liability: &BorrowerInfo,
+    block_height: u64,
+) -> Decimal256 {
+    let tenure = block_height.saturating_sub(liability.opened_at);
+    config
+        .reward_tenure_tiers
+        .iter()
+        .rev()
+        .find(|tier| tenure >= tier.min_tenure)
+        .map(|tier| tier.multiplier)
+        .unwrap_or_else(Decimal256::one)
+}
+
+/// Multiplier averaged over the blocks since the position's rewards
+/// were last settled, each block at the tier it had reached, over the
+/// top tier's. The reward index accrues evenly between settlements
+fn reward_tenure_weight(
+    config: &Config,
+    liability: &BorrowerInfo,
+    block_height: u64,
+) -> Decimal256 {
+    let top_multiplier = match config.reward_tenure_tiers.last() {
+        Some(tier) => tier.multiplier,
+        None => return Decimal256::one(),
+    };
+
+    let settled_at = liability.reward_updated_at;
+    if block_height <= settled_at {
+        return reward_tenure_multiplier(config, liability, block_height) / top_multiplier;
+    }
+
+    let tier_starts = config
+        .reward_tenure_tiers
+        .iter()
+        .map(|tier| liability.opened_at.saturating_add(tier.min_tenure))
+        .filter(|height| *height > settled_at && *height < block_height);
+
+    let mut weighted_blocks = Decimal256::zero();
+    let mut from = settled_at;
+    for to in tier_starts.chain(std::iter::once(block_height)) {
+        weighted_blocks +=
+            Decimal256::from_uint256(to - from) * reward_tenure_multiplier(config, liability, from);
+        from = to;
+    }
+
+    weighted_blocks / Decimal256::from_uint256(block_height - settled_at) / top_multiplier
 }
 
 pub fn query_borrower_info(
@@ -383,10 +1119,10 @@ pub fn query_borrower_info(
     let mut state: State = read_state(deps.storage)?;
 
     compute_interest(deps, &config, &mut state, block_height, None)?;
-    compute_borrower_interest(&state, &mut borrower_info);
+    settle_borrower_interest(&mut state, &mut borrower_info, &config.rounding_mode);
 
     compute_reward(&mut state, block_height);
-    compute_borrower_reward(&state, &mut borrower_info);
+    compute_borrower_reward(&config, &state, &mut borrower_info, block_height);
 
     Ok(BorrowerInfoResponse {
         borrower: borrower.to_string(),
@@ -394,6 +1130,152 @@ pub fn query_borrower_info(
         reward_index: borrower_info.reward_index,
         loan_amount: borrower_info.loan_amount,
         pending_rewards: borrower_info.pending_rewards,
+        fixed_rate: borrower_info.fixed_rate,
+    })
+}
+
+/// Rewards are spent in whole units, so only the floored amount
+/// is claimable; the distributor must hold that much ANC and allow
+/// it under its per spend limit
+pub fn query_claimable_reward(
+    deps: Deps,
+    env: Env,
+    borrower: Addr,
+) -> StdResult<ClaimableRewardResponse> {
+    let config: Config = read_config(deps.storage)?;
+    let borrower_info = query_borrower_info(deps, env, borrower.clone(), None)?;
+    let pending = borrower_info.pending_rewards * Uint256::one();
+
+    let distributor_contract = deps.api.addr_humanize(&config.distributor_contract)?;
+    let distributor_config = query_distributor_config(deps, distributor_contract.clone())?;
+    let distributor_balance = query_token_balance(
+        deps,
+        deps.api.addr_validate(&distributor_config.anchor_token)?,
+        distributor_contract,
+    )?;
+
+    Ok(ClaimableRewardResponse {
+        borrower: borrower.to_string(),
+        pending,
+        distributor_sufficient: pending <= distributor_balance
+            && pending <= Uint256::from(distributor_config.spend_limit),
+    })
+}
+
+/// The settled loan at `block_height`; a repay of exactly this amount in
+/// that block leaves a zero loan, since repaid amounts are whole units
+pub fn query_payoff_amount(
+    deps: Deps,
+    env: Env,
+    borrower: Addr,
+    block_height: Option<u64>,
+) -> StdResult<PayoffAmountResponse> {
+    let config: Config = read_config(deps.storage)?;
+    let borrower_info = query_borrower_info(deps, env, borrower.clone(), block_height)?;
+
+    Ok(PayoffAmountResponse {
+        borrower: borrower.to_string(),
+        payoff_amount: borrower_info.loan_amount,
+        tax_amount: sender_tax(deps, &config, borrower_info.loan_amount)?,
+    })
+}
+
+/// Settles every borrower at the current block, as each would be
+/// on its next interaction, and sums the resulting loan amounts
+pub fn query_integrity_check(deps: Deps, env: Env) -> StdResult<IntegrityCheckResponse> {
+    let config: Config = read_config(deps.storage)?;
+    let mut state: State = read_state(deps.storage)?;
+    compute_interest(deps, &config, &mut state, env.block.height, None)?;
+
+    let mut borrower_liabilities = Decimal256::zero();
+    for mut liability in read_all_borrower_infos(deps.storage)? {
+        settle_borrower_interest(&mut state, &mut liability, &config.rounding_mode);
+        borrower_liabilities += Decimal256::from_uint256(liability.loan_amount);
+    }
+
+    let total_liabilities = state.total_liabilities + state.total_fixed_liabilities;
+    let discrepancy = if total_liabilities > borrower_liabilities {
+        total_liabilities - borrower_liabilities
+    } else {
+        borrower_liabilities - total_liabilities
+    };
+
+    Ok(IntegrityCheckResponse {
+        total_liabilities,
+        borrower_liabilities,
+        discrepancy,
+    })
+}
+
+pub fn query_borrower_health(
+    deps: Deps,
+    env: Env,
+    borrower: Addr,
+) -> StdResult<BorrowerHealthResponse> {
+    let config: Config = read_config(deps.storage)?;
+    let borrower_info = query_borrower_info(deps, env.clone(), borrower.clone(), None)?;
+    let borrow_limit_res: BorrowLimitResponse = query_borrow_limit(
+        deps,
+        deps.api.addr_humanize(&config.overseer_contract)?,
+        borrower.clone(),
+        Some(env.block.time.seconds()),
+    )?;
+
+    let health_factor = if borrower_info.loan_amount.is_zero() {
+        None
+    } else {
+        Some(
+            Decimal256::from_uint256(borrow_limit_res.borrow_limit)
+                / Decimal256::from_uint256(borrower_info.loan_amount),
+        )
+    };
+
+    Ok(BorrowerHealthResponse {
+        borrower: borrower.to_string(),
+        loan_amount: borrower_info.loan_amount,
+        borrow_limit: borrow_limit_res.borrow_limit,
+        health_factor,
+    })
+}
+
+pub fn query_is_liquidatable(
+    deps: Deps,
+    env: Env,
+    borrower: Addr,
+) -> StdResult<IsLiquidatableResponse> {
+    let health = query_borrower_health(deps, env, borrower)?;
+    let shortfall = if health.loan_amount > health.borrow_limit {
+        health.loan_amount - health.borrow_limit
+    } else {
+        Uint256::zero()
+    };
+
+    Ok(IsLiquidatableResponse {
+        borrower: health.borrower,
+        liquidatable: !shortfall.is_zero(),
+        shortfall,
+    })
+}
+
+pub fn query_borrower_utilization(
+    deps: Deps,
+    env: Env,
+    borrower: Addr,
+) -> StdResult<BorrowerUtilizationResponse> {
+    let health = query_borrower_health(deps, env, borrower)?;
+    let utilization = if health.loan_amount >= health.borrow_limit {
+        if health.loan_amount.is_zero() {
+            Decimal256::zero()
+        } else {
+            Decimal256::one()
+        }
+    } else {
+        Decimal256::from_ratio(health.loan_amount, health.borrow_limit)
+    };
+
+    Ok(BorrowerUtilizationResponse {
+        borrower: health.borrower,
+        utilization,
     })
 }
 
@@ -408,7 +1290,61 @@ pub fn query_borrower_infos(
         None
     };
 
-    let borrower_infos: Vec<BorrowerInfoResponse> = read_borrower_infos(deps, start_after, limit)?;
+    let config: Config = read_config(deps.storage)?;
+    let borrower_infos: Vec<BorrowerInfoResponse> =
+        read_borrower_infos(deps, start_after, limit, config.max_borrower_infos_limit)?;
+    Ok(BorrowerInfosResponse { borrower_infos })
+}
+
+/// Max # of borrowers accepted by a single BorrowerInfosByList query
+const MAX_BORROWERS_BY_LIST: usize = 30;
+
+/// Unknown borrowers are returned as zeroed entries, the same as the
+/// BorrowerInfo query, so responses line up with the requested list
+pub fn query_borrower_infos_by_list(
+    deps: Deps,
+    env: Env,
+    borrowers: Vec<Addr>,
+    block_height: Option<u64>,
+) -> StdResult<BorrowerInfosResponse> {
+    if borrowers.len() > MAX_BORROWERS_BY_LIST {
+        return Err(StdError::generic_err(format!(
+            "Cannot query more than {} borrowers at once",
+            MAX_BORROWERS_BY_LIST
+        )));
+    }
+
+    let block_height = block_height.unwrap_or(env.block.height);
+
+    let config: Config = read_config(deps.storage)?;
+    let mut state: State = read_state(deps.storage)?;
+    compute_interest(deps, &config, &mut state, block_height, None)?;
+    compute_reward(&mut state, block_height);
+
+    let borrower_infos = borrowers
+        .into_iter()
+        .map(|borrower| {
+            let mut borrower_info: BorrowerInfo = read_borrower_info(
+                deps.storage,
+                &deps.api.addr_canonicalize(borrower.as_str())?,
+            );
+
+            // settle against a copy so each borrower sees the same accrued state
+            let mut state = state.clone();
+            settle_borrower_interest(&mut state, &mut borrower_info, &config.rounding_mode);
+            compute_borrower_reward(&config, &state, &mut borrower_info, block_height);
+
+            Ok(BorrowerInfoResponse {
+                borrower: borrower.to_string(),
+                interest_index: borrower_info.interest_index,
+                reward_index: borrower_info.reward_index,
+                loan_amount: borrower_info.loan_amount,
+                pending_rewards: borrower_info.pending_rewards,
+                fixed_rate: borrower_info.fixed_rate,
+            })
+        })
+        .collect::<StdResult<Vec<BorrowerInfoResponse>>>()?;
+
     Ok(BorrowerInfosResponse { borrower_infos })
 }
 
@@ -420,23 +1356,98 @@ fn assert_max_borrow_factor(
 ) -> Result<(), ContractError> {
     let current_balance = Decimal256::from_uint256(current_balance);
     let borrow_amount = Decimal256::from_uint256(borrow_amount);
+    let total_liabilities = state.total_liabilities + state.total_fixed_liabilities;
 
     // Assert max borrow factor
-    if state.total_liabilities + borrow_amount
-        > (current_balance + state.total_liabilities - state.total_reserves)
-            * config.max_borrow_factor
-    {
-        return Err(ContractError::MaxBorrowFactorReached(
-            config.stable_denom.clone(),
-        ));
+    let deposits = current_balance + total_liabilities - state.total_reserves;
+    let max_liabilities =
+        deposits * effective_max_borrow_factor(config, total_liabilities, deposits);
+    if total_liabilities + borrow_amount > max_liabilities {
+        let borrowable = if max_liabilities > total_liabilities {
+            max_liabilities - total_liabilities
+        } else {
+            Decimal256::zero()
+        };
+
+        return Err(ContractError::MaxBorrowFactorReached {
+            denom: config.stable_denom.clone(),
+            requested: (borrow_amount * Uint256::one()).into(),
+            borrowable: (borrowable * Uint256::one()).into(),
+        });
     }
 
-    // Assert available balance
-    if borrow_amount + state.total_reserves > current_balance {
-        return Err(ContractError::NoStableAvailable(
-            config.stable_denom.clone(),
-        ));
+    Ok(())
+}
+
+/// base_borrow_factor * (1 - utilization_sensitivity * utilization),
+/// capped at max_borrow_factor, with utilization = liabilities / deposits
+/// before the borrow
+pub(crate) fn effective_max_borrow_factor(
+    config: &Config,
+    total_liabilities: Decimal256,
+    deposits: Decimal256,
+) -> Decimal256 {
+    if config.utilization_sensitivity.is_zero() || deposits.is_zero() {
+        return config.max_borrow_factor;
+    }
+
+    let tightening = config.utilization_sensitivity * total_liabilities / deposits;
+    if tightening >= Decimal256::one() {
+        return Decimal256::zero();
+    }
+
+    std::cmp::min(
+        config.base_borrow_factor * (Decimal256::one() - tightening),
+        config.max_borrow_factor,
+    )
+}
+
+/// Stable left available after the borrow, excluding reserves,
+/// must stay above `min_liquidity_ratio` of total deposits
+fn assert_min_liquidity_ratio(
+    config: &Config,
+    state: &State,
+    current_balance: Uint256,
+    borrow_amount: Uint256,
+) -> Result<(), ContractError> {
+    if config.min_liquidity_ratio.is_zero() {
+        return Ok(());
+    }
+
+    let current_balance = Decimal256::from_uint256(current_balance);
+    let borrow_amount = Decimal256::from_uint256(borrow_amount);
+    let total_deposits = current_balance + state.total_liabilities + state.total_fixed_liabilities
+        - state.total_reserves;
+    let floor = total_deposits * config.min_liquidity_ratio;
+
+    // borrow_stable ensures the balance covers reserves and the borrow
+    let available = current_balance - state.total_reserves - borrow_amount;
+    if available < floor {
+        return Err(ContractError::LiquidityRatioFloor {
+            available: (available * Uint256::one()).into(),
+            floor: (floor * Uint256::one()).into(),
+        });
     }
 
     Ok(())
 }
+
+/// Stable available to borrowers and redeemers is the balance not held as reserves
+pub(crate) fn no_stable_available(
+    config: &Config,
+    state: &State,
+    current_balance: Decimal256,
+    requested: Decimal256,
+) -> ContractError {
+    let available = if current_balance > state.total_reserves {
+        current_balance - state.total_reserves
+    } else {
+        Decimal256::zero()
+    };
+
+    ContractError::NoStableAvailable {
+        denom: config.stable_denom.clone(),
+        requested: (requested * Uint256::one()).into(),
+        available: (available * Uint256::one()).into(),
+    }
+}
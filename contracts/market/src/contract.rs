@@ -3,32 +3,68 @@ use cosmwasm_std::entry_point;
 
 use crate::borrow::{
     borrow_stable, claim_rewards, compute_interest, compute_interest_raw, compute_reward,
-    query_borrower_info, query_borrower_infos, repay_stable, repay_stable_from_liquidation,
+    mark_fallback, query_borrower_health, query_borrower_info, query_borrower_infos,
+    query_borrower_infos_by_list, query_borrower_utilization, query_claimable_reward,
+    query_integrity_check, query_is_liquidatable, query_payoff_amount, repay, repay_stable,
+    repay_stable_from_liquidation, resync_borrowers, set_borrow_delegate, set_liquidation_callback,
+    LIQUIDATION_CALLBACK_REPLY_ID,
+};
+use crate::deposit::{
+    compute_exchange_rate_raw, deposit, deposit_and_stake, deposit_protocol_owned, deposit_stable,
+    deposit_stable_locked, deposit_stable_split, query_aterra_to_underlying, query_locked_deposit,
+    query_referral_volume, query_remaining_deposit_capacity, query_total_deposits,
+    query_tvl_in_reference, query_underlying_to_aterra, redeem_locked, redeem_stable, MintTo,
 };
-use crate::deposit::{compute_exchange_rate_raw, deposit_stable, redeem_stable};
 use crate::error::ContractError;
-use crate::querier::{query_anc_emission_rate, query_borrow_rate, query_target_deposit_rate};
+use crate::proposal::{execute_param, propose_param, query_proposal, vote_param};
+use crate::querier::{
+    cached_market_borrow_rate, query_anc_emission_rate, query_target_deposit_rate,
+    query_token_symbol,
+};
+use crate::rates::{
+    query_borrower_rate, query_net_deposit_rate, query_rate_spread, query_rates, BLOCKS_PER_YEAR,
+};
 use crate::response::MsgInstantiateContractResponse;
-use crate::state::{read_config, read_state, store_config, store_state, Config, State};
+use crate::snapshot::MarketSnapshot;
+use crate::stable::{
+    query_stable_balance, query_tax_amount, received_stable_cw20, stable_transfer_msg,
+    track_stable_cw20_balance,
+};
+use crate::state::{
+    read_config, read_liability_history, read_pending_config_changes,
+    read_reserve_transfer_history, read_reward_index_history, read_state, store_config,
+    store_liability_snapshot, store_pending_config_changes, store_reserve_transfer,
+    store_reward_index_snapshot, store_state, Config, PendingConfigChange, State, HISTORY_CAPACITY,
+    MAX_LIMIT,
+};
 
 use cosmwasm_bignumber::{Decimal256, Uint256};
 use cosmwasm_std::{
     attr, from_binary, to_binary, Addr, BankMsg, Binary, CanonicalAddr, Coin, CosmosMsg, Deps,
     DepsMut, Env, MessageInfo, Reply, Response, StdError, StdResult, SubMsg, Uint128, WasmMsg,
 };
-use cw20::{Cw20Coin, Cw20ReceiveMsg, MinterResponse};
+use cw20::{Cw20Coin, Cw20ExecuteMsg, Cw20ReceiveMsg, MinterResponse};
 
 use moneymarket::common::optional_addr_validate;
-use moneymarket::interest_model::BorrowRateResponse;
 use moneymarket::market::{
-    ConfigResponse, Cw20HookMsg, EpochStateResponse, ExecuteMsg, InstantiateMsg, QueryMsg,
-    StateResponse,
+    Compounding, ConfigAndStateResponse, ConfigAuditResponse, ConfigChange, ConfigResponse,
+    ContractKind, Cw20HookMsg, EpochStateResponse, ExecuteMsg, FreshnessResponse,
+    HistoricalStateResponse, InstantiateMsg, LiabilityHistoryResponse, LiabilitySnapshot,
+    MarketSummaryResponse, PendingConfigChangeResponse, PendingConfigChangesResponse,
+    ProjectedReservesResponse, QueryMsg, ReserveTransfer, ReserveTransferHistoryResponse,
+    RewardIndexHistoryResponse, RewardIndexSnapshot, RewardStateResponse, RewardTenureTier,
+    RoundingMode, StateResponse,
 };
-use moneymarket::querier::{deduct_tax, query_balance, query_supply};
+use moneymarket::querier::{deduct_tax, query_balance, query_supply, query_token_balance};
 use protobuf::Message;
 use terraswap::token::InstantiateMsg as TokenInstantiateMsg;
 
 pub const INITIAL_DEPOSIT_AMOUNT: u128 = 1000000;
+/// Address of 20 zero bytes, which no key controls
+pub const SEED_DEAD_ADDR: &str = "terra1qqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqq486l9a";
+/// # of blocks a queued config change waits before it can be applied, about a week
+pub const CONFIG_TIMELOCK: u64 = 100800;
+pub const RESERVE_TRANSFER_REPLY_ID: u64 = 3;
 
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn instantiate(
@@ -37,19 +73,61 @@ pub fn instantiate(
     info: MessageInfo,
     msg: InstantiateMsg,
 ) -> Result<Response, ContractError> {
-    let initial_deposit = info
-        .funds
-        .iter()
-        .find(|c| c.denom == msg.stable_denom)
-        .map(|c| c.amount)
-        .unwrap_or_else(Uint128::zero);
-
-    if initial_deposit != Uint128::from(INITIAL_DEPOSIT_AMOUNT) {
-        return Err(ContractError::InitialFundsNotDeposited(
-            INITIAL_DEPOSIT_AMOUNT,
-            msg.stable_denom,
-        ));
-    }
+    // A cw20 stable cannot be sent along with instantiation,
+    // so cw20 markets start without the initial seed deposit
+    let (name_template, denom_name, symbol, initial_deposit) = if msg.stable_cw20 {
+        let stable_symbol =
+            query_token_symbol(deps.as_ref(), deps.api.addr_validate(&msg.stable_denom)?)?;
+        (
+            "Anchor {DENOM}",
+            stable_symbol.clone(),
+            format!("a{}", stable_symbol),
+            Uint128::zero(),
+        )
+    } else {
+        validate_stable_denom(&msg.stable_denom)?;
+
+        let initial_deposit = info
+            .funds
+            .iter()
+            .find(|c| c.denom == msg.stable_denom)
+            .map(|c| c.amount)
+            .unwrap_or_else(Uint128::zero);
+
+        if initial_deposit != Uint128::from(INITIAL_DEPOSIT_AMOUNT) {
+            return Err(ContractError::InitialFundsNotDeposited {
+                expected: INITIAL_DEPOSIT_AMOUNT,
+                actual: initial_deposit.u128(),
+                denom: msg.stable_denom,
+            });
+        }
+
+        (
+            "Anchor Terra {DENOM}",
+            msg.stable_denom[1..].to_uppercase(),
+            format!(
+                "a{}T",
+                msg.stable_denom[1..(msg.stable_denom.len() - 1)].to_uppercase()
+            ),
+            initial_deposit,
+        )
+    };
+
+    let name = msg
+        .aterra_name
+        .as_deref()
+        .unwrap_or(name_template)
+        .replace("{DENOM}", &denom_name);
+    validate_aterra_name(&name)?;
+
+    let symbol = symbol + msg.symbol_suffix.as_deref().unwrap_or_default();
+    validate_aterra_symbol(&symbol)?;
+    validate_max_borrow_factor(msg.max_borrow_factor)?;
+
+    let aterra_admin = match msg.aterra_admin {
+        Some(aterra_admin) => deps.api.addr_validate(&aterra_admin)?.to_string(),
+        None => env.contract.address.to_string(),
+    };
 
     store_config(
         deps.storage,
@@ -64,6 +142,40 @@ pub fn instantiate(
             distributor_contract: CanonicalAddr::from(vec![]),
             stable_denom: msg.stable_denom.clone(),
             max_borrow_factor: msg.max_borrow_factor,
+            borrow_grace_period: 0,
+            epoch_caller: None,
+            borrow_cooldown: 0,
+            redeem_lockup: 0,
+            stable_cw20: msg.stable_cw20,
+            tax_cap_override: None,
+            min_liquidity_ratio: Decimal256::zero(),
+            liquidation_fee_rate: Decimal256::zero(),
+            max_borrower_infos_limit: MAX_LIMIT,
+            fallback_interest_model: None,
+            fallback_borrow_rate: None,
+            utilization_sensitivity: Decimal256::zero(),
+            reward_tenure_tiers: vec![],
+            reserve_target: None,
+            reserve_excess_recipient: None,
+            max_reserves: None,
+            max_borrow_per_tx: None,
+            guardians: vec![],
+            guardian_threshold: 0,
+            claims_paused: false,
+            rate_refresh_interval: 0,
+            min_anc_emission_rate: Decimal256::zero(),
+            low_liquidity_haircut: None,
+            max_borrow_per_block: None,
+            locked_deposit_boost: Decimal256::zero(),
+            burn_redeem_dust: false,
+            strict_supply_check: false,
+            blocks_per_year: BLOCKS_PER_YEAR,
+            max_total_deposits: None,
+            redeem_fast_path: false,
+            rounding_mode: RoundingMode::Floor,
+            partial_fill_borrows: false,
+            staking_contracts: vec![],
+            base_borrow_factor: msg.max_borrow_factor,
         },
     )?;
 
@@ -77,42 +189,72 @@ pub fn instantiate(
             global_interest_index: Decimal256::one(),
             global_reward_index: Decimal256::zero(),
             anc_emission_rate: msg.anc_emission_rate,
-            prev_aterra_supply: Uint256::zero(),
+            prev_aterra_supply: Uint256::from(initial_deposit),
             prev_exchange_rate: Decimal256::one(),
+            total_fixed_liabilities: Decimal256::zero(),
+            fixed_interest_per_block: Decimal256::zero(),
+            protocol_owned_aterra: Uint256::from(initial_deposit),
+            pending_reserve_transfer: Uint256::zero(),
+            cached_borrow_rate: None,
+            borrow_rate_cached_at: 0,
+            peak_utilization: Decimal256::zero(),
+            borrowed_this_block: Uint256::zero(),
+            borrowed_at_block: 0,
+            cumulative_reserves_collected: Uint256::zero(),
+            stable_cw20_balance: Uint256::zero(),
+            borrower_count: 0,
         },
     )?;
 
-    Ok(
-        Response::new().add_submessages(vec![SubMsg::reply_on_success(
-            CosmosMsg::Wasm(WasmMsg::Instantiate {
-                admin: None,
-                code_id: msg.aterra_code_id,
-                funds: vec![],
-                label: "".to_string(),
-                msg: to_binary(&TokenInstantiateMsg {
-                    name: format!("Anchor Terra {}", msg.stable_denom[1..].to_uppercase()),
-                    symbol: format!(
-                        "a{}T",
-                        msg.stable_denom[1..(msg.stable_denom.len() - 1)].to_uppercase()
-                    ),
-                    decimals: 6u8,
-                    initial_balances: vec![Cw20Coin {
-                        address: env.contract.address.to_string(),
-                        amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT),
-                    }],
-                    mint: Some(MinterResponse {
-                        minter: env.contract.address.to_string(),
-                        cap: None,
-                    }),
-                })?,
-            }),
-            1,
-        )]),
-    )
+    let initial_balances = if initial_deposit.is_zero() {
+        vec![]
+    } else {
+        vec![Cw20Coin {
+            address: if msg.lock_seed {
+                SEED_DEAD_ADDR.to_string()
+            } else {
+                env.contract.address.to_string()
+            },
+            amount: initial_deposit,
+        }]
+    };
+
+    // Reply on error too, so a failed aterra instantiation
+    // surfaces as a market error
+    Ok(Response::new().add_submessages(vec![SubMsg::reply_always(
+        CosmosMsg::Wasm(WasmMsg::Instantiate {
+            admin: Some(aterra_admin),
+            code_id: msg.aterra_code_id,
+            funds: vec![],
+            label: "".to_string(),
+            msg: to_binary(&TokenInstantiateMsg {
+                name,
+                symbol,
+                decimals: 6u8,
+                initial_balances,
+                mint: Some(MinterResponse {
+                    minter: env.contract.address.to_string(),
+                    cap: None,
+                }),
+            })?,
+        }),
+        1,
+    )]))
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn execute(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: ExecuteMsg,
+) -> Result<Response, ContractError> {
+    let res = dispatch_execute(deps.branch(), env, info, msg)?;
+    track_stable_cw20_balance(deps, &res)?;
+    Ok(res)
+}
+
+fn dispatch_execute(
     deps: DepsMut,
     env: Env,
     info: MessageInfo,
@@ -138,20 +280,128 @@ pub fn execute(
             )
         }
         ExecuteMsg::UpdateConfig {
-            owner_addr,
-            interest_model,
             distribution_model,
-            max_borrow_factor,
+            borrow_grace_period,
+            epoch_caller,
+            borrow_cooldown,
+            redeem_lockup,
+            tax_cap_override,
+            min_liquidity_ratio,
+            liquidation_fee_rate,
+            max_borrower_infos_limit,
+            fallback_interest_model,
+            fallback_borrow_rate,
+            utilization_sensitivity,
+            reward_tenure_tiers,
+            reserve_target,
+            reserve_excess_recipient,
+            max_reserves,
+            max_borrow_per_tx,
+            guardians,
+            guardian_threshold,
+            claims_paused,
+            rate_refresh_interval,
+            min_anc_emission_rate,
+            low_liquidity_haircut,
+            max_borrow_per_block,
+            locked_deposit_boost,
+            burn_redeem_dust,
+            strict_supply_check,
+            blocks_per_year,
+            max_total_deposits,
+            redeem_fast_path,
+            rounding_mode,
+            partial_fill_borrows,
+            staking_contracts,
+            base_borrow_factor,
         } => {
             let api = deps.api;
             update_config(
                 deps,
-                env,
                 info,
-                optional_addr_validate(api, owner_addr)?,
-                optional_addr_validate(api, interest_model)?,
                 optional_addr_validate(api, distribution_model)?,
-                max_borrow_factor,
+                borrow_grace_period,
+                optional_addr_validate(api, epoch_caller)?,
+                borrow_cooldown,
+                redeem_lockup,
+                tax_cap_override,
+                min_liquidity_ratio,
+                liquidation_fee_rate,
+                max_borrower_infos_limit,
+                optional_addr_validate(api, fallback_interest_model)?,
+                fallback_borrow_rate,
+                utilization_sensitivity,
+                reward_tenure_tiers,
+                reserve_target,
+                optional_addr_validate(api, reserve_excess_recipient)?,
+                max_reserves,
+                max_borrow_per_tx,
+                guardians
+                    .map(|guardians| {
+                        guardians
+                            .iter()
+                            .map(|guardian| api.addr_validate(guardian))
+                            .collect::<StdResult<Vec<Addr>>>()
+                    })
+                    .transpose()?,
+                guardian_threshold,
+                claims_paused,
+                rate_refresh_interval,
+                min_anc_emission_rate,
+                low_liquidity_haircut,
+                max_borrow_per_block,
+                locked_deposit_boost,
+                burn_redeem_dust,
+                strict_supply_check,
+                blocks_per_year,
+                max_total_deposits,
+                redeem_fast_path,
+                rounding_mode,
+                partial_fill_borrows,
+                staking_contracts
+                    .map(|staking_contracts| {
+                        staking_contracts
+                            .iter()
+                            .map(|staking_contract| api.addr_validate(staking_contract))
+                            .collect::<StdResult<Vec<Addr>>>()
+                    })
+                    .transpose()?,
+                base_borrow_factor,
+            )
+        }
+        ExecuteMsg::QueueConfigChange { change, eta } => {
+            queue_config_change(deps, env, info, change, eta)
+        }
+        ExecuteMsg::ApplyConfigChange {} => apply_config_change(deps, env, info),
+        ExecuteMsg::SweepToken { denom, recipient } => {
+            let api = deps.api;
+            sweep_token(deps, env, info, denom, api.addr_validate(&recipient)?)
+        }
+        ExecuteMsg::SweepCw20Token {
+            contract_addr,
+            recipient,
+        } => {
+            let api = deps.api;
+            sweep_cw20_token(
+                deps,
+                env,
+                info,
+                api.addr_validate(&contract_addr)?,
+                api.addr_validate(&recipient)?,
+            )
+        }
+        ExecuteMsg::SetLastInterestUpdated { block_height } => {
+            set_last_interest_updated(deps, env, info, block_height)
+        }
+        ExecuteMsg::ResetPeakUtilization {} => reset_peak_utilization(deps, info),
+        ExecuteMsg::ResyncBorrowers { start_after, limit } => {
+            let api = deps.api;
+            resync_borrowers(
+                deps,
+                env,
+                info,
+                optional_addr_validate(api, start_after)?,
+                limit,
             )
         }
         ExecuteMsg::ExecuteEpochOperations {
@@ -159,6 +409,7 @@ pub fn execute(
             target_deposit_rate,
             threshold_deposit_rate,
             distributed_interest,
+            reserve_only,
         } => execute_epoch_operations(
             deps,
             env,
@@ -167,9 +418,45 @@ pub fn execute(
             target_deposit_rate,
             threshold_deposit_rate,
             distributed_interest,
+            reserve_only,
         ),
-        ExecuteMsg::DepositStable {} => deposit_stable(deps, env, info),
-        ExecuteMsg::BorrowStable { borrow_amount, to } => {
+        ExecuteMsg::RegisterContract { which, address } => {
+            let api = deps.api;
+            register_contract(deps, info, which, api.addr_validate(&address)?)
+        }
+        ExecuteMsg::TransferReserves {} => transfer_reserves(deps, env),
+        ExecuteMsg::DepositStable { referrer } => {
+            let api = deps.api;
+            deposit_stable(deps, env, info, optional_addr_validate(api, referrer)?)
+        }
+        ExecuteMsg::DepositProtocolOwned {} => deposit_protocol_owned(deps, env, info),
+        ExecuteMsg::DepositAndStake {
+            staking_contract,
+            msg,
+        } => {
+            let api = deps.api;
+            deposit_and_stake(deps, env, info, api.addr_validate(&staking_contract)?, msg)
+        }
+        ExecuteMsg::DepositStableLocked { term_blocks } => {
+            deposit_stable_locked(deps, env, info, term_blocks)
+        }
+        ExecuteMsg::DepositStableSplit { recipients } => {
+            let recipients = recipients
+                .into_iter()
+                .map(|(recipient, amount)| {
+                    Ok((deps.api.addr_validate(&recipient)?, Uint256::from(amount)))
+                })
+                .collect::<StdResult<Vec<(Addr, Uint256)>>>()?;
+            deposit_stable_split(deps, env, info, recipients)
+        }
+        ExecuteMsg::RedeemLocked { lock_id } => redeem_locked(deps, env, info, lock_id),
+        ExecuteMsg::BorrowStable {
+            borrow_amount,
+            to,
+            fixed_rate,
+            hook,
+            on_behalf_of,
+        } => {
             let api = deps.api;
             borrow_stable(
                 deps,
@@ -177,9 +464,12 @@ pub fn execute(
                 info,
                 borrow_amount,
                 optional_addr_validate(api, to)?,
+                fixed_rate,
+                hook,
+                optional_addr_validate(api, on_behalf_of)?,
             )
         }
-        ExecuteMsg::RepayStable {} => repay_stable(deps, env, info),
+        ExecuteMsg::RepayStable { aterra_amount } => repay_stable(deps, env, info, aterra_amount),
         ExecuteMsg::RepayStableFromLiquidation {
             borrower,
             prev_balance,
@@ -197,27 +487,42 @@ pub fn execute(
             let api = deps.api;
             claim_rewards(deps, env, info, optional_addr_validate(api, to)?)
         }
+        ExecuteMsg::SetLiquidationCallback { msg } => set_liquidation_callback(deps, info, msg),
+        ExecuteMsg::SetBorrowDelegate { delegate, limit } => {
+            let api = deps.api;
+            set_borrow_delegate(deps, info, optional_addr_validate(api, delegate)?, limit)
+        }
+        ExecuteMsg::ProposeParam { param, value } => propose_param(deps, info, param, value),
+        ExecuteMsg::VoteParam {
+            proposal_id,
+            approve,
+        } => vote_param(deps, info, proposal_id, approve),
+        ExecuteMsg::ExecuteParam { proposal_id } => execute_param(deps, proposal_id),
     }
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn reply(deps: DepsMut, _env: Env, msg: Reply) -> Result<Response, ContractError> {
+pub fn reply(deps: DepsMut, env: Env, msg: Reply) -> Result<Response, ContractError> {
     match msg.id {
         1 => {
             // get new token's contract address
-            let res: MsgInstantiateContractResponse = Message::parse_from_bytes(
-                msg.result.unwrap().data.unwrap().as_slice(),
-            )
-            .map_err(|_| {
-                ContractError::Std(StdError::parse_err(
-                    "MsgInstantiateContractResponse",
-                    "failed to parse data",
-                ))
-            })?;
+            let data = msg
+                .result
+                .into_result()
+                .map_err(ContractError::AterraInstantiationFailed)?
+                .data
+                .ok_or(ContractError::InvalidReplyData {})?;
+            let res: MsgInstantiateContractResponse = Message::parse_from_bytes(data.as_slice())
+                .map_err(|_| ContractError::InvalidReplyData {})?;
             let token_addr = Addr::unchecked(res.get_contract_address());
 
             register_aterra(deps, token_addr)
         }
+        // a failed liquidation callback must not revert the liquidation
+        LIQUIDATION_CALLBACK_REPLY_ID => {
+            Ok(Response::new().add_attributes(vec![attr("action", "liquidation_callback_failed")]))
+        }
+        RESERVE_TRANSFER_REPLY_ID => reserve_transfer_reply(deps, env, msg.result.is_err()),
         _ => Err(ContractError::InvalidReplyId {}),
     }
 }
@@ -230,6 +535,36 @@ pub fn receive_cw20(
 ) -> Result<Response, ContractError> {
     let contract_addr = info.sender;
     match from_binary(&cw20_msg.msg) {
+        Ok(Cw20HookMsg::DepositStable { referrer }) => {
+            // only the cw20 stable can execute this message
+            let config: Config = read_config(deps.storage)?;
+            if !config.stable_cw20 || contract_addr.as_str() != config.stable_denom {
+                return Err(ContractError::Unauthorized {});
+            }
+
+            let received_amount = received_stable_cw20(deps.as_ref(), &config, cw20_msg.amount)?;
+            let cw20_sender_addr = deps.api.addr_validate(&cw20_msg.sender)?;
+            let referrer = optional_addr_validate(deps.api, referrer)?;
+            deposit(
+                deps,
+                env,
+                cw20_sender_addr,
+                received_amount,
+                referrer,
+                MintTo::Depositor,
+            )
+        }
+        Ok(Cw20HookMsg::RepayStable {}) => {
+            // only the cw20 stable can execute this message
+            let config: Config = read_config(deps.storage)?;
+            if !config.stable_cw20 || contract_addr.as_str() != config.stable_denom {
+                return Err(ContractError::Unauthorized {});
+            }
+
+            let received_amount = received_stable_cw20(deps.as_ref(), &config, cw20_msg.amount)?;
+            let cw20_sender_addr = deps.api.addr_validate(&cw20_msg.sender)?;
+            repay(deps, env, cw20_sender_addr, received_amount)
+        }
         Ok(Cw20HookMsg::RedeemStable {}) => {
             // only asset contract can execute this message
             let config: Config = read_config(deps.storage)?;
@@ -238,7 +573,23 @@ pub fn receive_cw20(
             }
 
             let cw20_sender_addr = deps.api.addr_validate(&cw20_msg.sender)?;
-            redeem_stable(deps, env, cw20_sender_addr, cw20_msg.amount)
+            redeem_stable(deps, env, cw20_sender_addr, cw20_msg.amount, None)
+        }
+        Ok(Cw20HookMsg::RedeemUnderlying { underlying_amount }) => {
+            // only asset contract can execute this message
+            let config: Config = read_config(deps.storage)?;
+            if deps.api.addr_canonicalize(contract_addr.as_str())? != config.aterra_contract {
+                return Err(ContractError::Unauthorized {});
+            }
+
+            let cw20_sender_addr = deps.api.addr_validate(&cw20_msg.sender)?;
+            redeem_stable(
+                deps,
+                env,
+                cw20_sender_addr,
+                cw20_msg.amount,
+                Some(underlying_amount),
+            )
         }
         _ => Err(ContractError::MissingRedeemStableHook {}),
     }
@@ -281,17 +632,89 @@ pub fn register_contracts(
     config.distributor_contract = deps.api.addr_canonicalize(distributor_contract.as_str())?;
     store_config(deps.storage, &config)?;
 
-    Ok(Response::default())
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "register_contracts"),
+        attr("overseer", overseer_contract),
+        attr("interest_model", interest_model),
+        attr("distribution_model", distribution_model),
+        attr("collector", collector_contract),
+        attr("distributor", distributor_contract),
+    ]))
+}
+
+pub fn register_contract(
+    deps: DepsMut,
+    info: MessageInfo,
+    which: ContractKind,
+    address: Addr,
+) -> Result<Response, ContractError> {
+    let mut config: Config = read_config(deps.storage)?;
+
+    // permission check
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner_addr {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let slot = match which {
+        ContractKind::Overseer => &mut config.overseer_contract,
+        ContractKind::InterestModel => &mut config.interest_model,
+        ContractKind::DistributionModel => &mut config.distribution_model,
+        ContractKind::Collector => &mut config.collector_contract,
+        ContractKind::Distributor => &mut config.distributor_contract,
+    };
+
+    if *slot != CanonicalAddr::from(vec![]) {
+        return Err(ContractError::ContractAlreadyRegistered(which.to_string()));
+    }
+
+    *slot = deps.api.addr_canonicalize(address.as_str())?;
+    store_config(deps.storage, &config)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "register_contract"),
+        attr("which", which.to_string()),
+        attr("address", address),
+    ]))
 }
 
+#[allow(clippy::too_many_arguments)]
 pub fn update_config(
     deps: DepsMut,
-    env: Env,
     info: MessageInfo,
-    owner_addr: Option<Addr>,
-    interest_model: Option<Addr>,
     distribution_model: Option<Addr>,
-    max_borrow_factor: Option<Decimal256>,
+    borrow_grace_period: Option<u64>,
+    epoch_caller: Option<Addr>,
+    borrow_cooldown: Option<u64>,
+    redeem_lockup: Option<u64>,
+    tax_cap_override: Option<Uint128>,
+    min_liquidity_ratio: Option<Decimal256>,
+    liquidation_fee_rate: Option<Decimal256>,
+    max_borrower_infos_limit: Option<u32>,
+    fallback_interest_model: Option<Addr>,
+    fallback_borrow_rate: Option<Decimal256>,
+    utilization_sensitivity: Option<Decimal256>,
+    reward_tenure_tiers: Option<Vec<RewardTenureTier>>,
+    reserve_target: Option<Uint256>,
+    reserve_excess_recipient: Option<Addr>,
+    max_reserves: Option<Uint256>,
+    max_borrow_per_tx: Option<Uint256>,
+    guardians: Option<Vec<Addr>>,
+    guardian_threshold: Option<u32>,
+    claims_paused: Option<bool>,
+    rate_refresh_interval: Option<u64>,
+    min_anc_emission_rate: Option<Decimal256>,
+    low_liquidity_haircut: Option<Decimal256>,
+    max_borrow_per_block: Option<Uint256>,
+    locked_deposit_boost: Option<Decimal256>,
+    burn_redeem_dust: Option<bool>,
+    strict_supply_check: Option<bool>,
+    blocks_per_year: Option<u64>,
+    max_total_deposits: Option<Uint256>,
+    redeem_fast_path: Option<bool>,
+    rounding_mode: Option<RoundingMode>,
+    partial_fill_borrows: Option<bool>,
+    staking_contracts: Option<Vec<Addr>>,
+    base_borrow_factor: Option<Decimal256>,
 ) -> Result<Response, ContractError> {
     let mut config: Config = read_config(deps.storage)?;
 
@@ -300,99 +723,312 @@ pub fn update_config(
         return Err(ContractError::Unauthorized {});
     }
 
-    if let Some(owner_addr) = owner_addr {
-        config.owner_addr = deps.api.addr_canonicalize(owner_addr.as_str())?;
+    if let Some(distribution_model) = distribution_model {
+        config.distribution_model = deps.api.addr_canonicalize(distribution_model.as_str())?;
     }
 
-    if interest_model.is_some() {
-        let mut state: State = read_state(deps.storage)?;
-        compute_interest(deps.as_ref(), &config, &mut state, env.block.height, None)?;
-        store_state(deps.storage, &state)?;
+    if let Some(borrow_grace_period) = borrow_grace_period {
+        config.borrow_grace_period = borrow_grace_period;
+    }
+
+    if let Some(epoch_caller) = epoch_caller {
+        config.epoch_caller = Some(deps.api.addr_canonicalize(epoch_caller.as_str())?);
+    }
+
+    if let Some(borrow_cooldown) = borrow_cooldown {
+        config.borrow_cooldown = borrow_cooldown;
+    }
+
+    if let Some(redeem_lockup) = redeem_lockup {
+        config.redeem_lockup = redeem_lockup;
+    }
+
+    if let Some(tax_cap_override) = tax_cap_override {
+        config.tax_cap_override = Some(tax_cap_override);
+    }
+
+    if let Some(min_liquidity_ratio) = min_liquidity_ratio {
+        config.min_liquidity_ratio = min_liquidity_ratio;
+    }
+
+    if let Some(liquidation_fee_rate) = liquidation_fee_rate {
+        validate_liquidation_fee_rate(liquidation_fee_rate)?;
+        config.liquidation_fee_rate = liquidation_fee_rate;
+    }
+
+    if let Some(max_borrower_infos_limit) = max_borrower_infos_limit {
+        config.max_borrower_infos_limit = max_borrower_infos_limit;
+    }
+
+    if let Some(fallback_interest_model) = fallback_interest_model {
+        config.fallback_interest_model = Some(
+            deps.api
+                .addr_canonicalize(fallback_interest_model.as_str())?,
+        );
+    }
+
+    if let Some(fallback_borrow_rate) = fallback_borrow_rate {
+        config.fallback_borrow_rate = Some(fallback_borrow_rate);
+    }
+
+    if let Some(utilization_sensitivity) = utilization_sensitivity {
+        config.utilization_sensitivity = utilization_sensitivity;
+    }
+
+    if let Some(reward_tenure_tiers) = reward_tenure_tiers {
+        validate_reward_tenure_tiers(&reward_tenure_tiers)?;
+        config.reward_tenure_tiers = reward_tenure_tiers;
+    }
+
+    if let Some(reserve_target) = reserve_target {
+        config.reserve_target = Some(reserve_target);
+    }
+
+    if let Some(reserve_excess_recipient) = reserve_excess_recipient {
+        config.reserve_excess_recipient = Some(
+            deps.api
+                .addr_canonicalize(reserve_excess_recipient.as_str())?,
+        );
+    }
+
+    if let Some(max_reserves) = max_reserves {
+        config.max_reserves = Some(max_reserves);
+    }
+
+    if let Some(max_borrow_per_tx) = max_borrow_per_tx {
+        config.max_borrow_per_tx = Some(max_borrow_per_tx);
+    }
+
+    if let Some(guardians) = guardians {
+        config.guardians = guardians
+            .iter()
+            .map(|guardian| deps.api.addr_canonicalize(guardian.as_str()))
+            .collect::<StdResult<Vec<CanonicalAddr>>>()?;
+    }
+
+    if let Some(guardian_threshold) = guardian_threshold {
+        config.guardian_threshold = guardian_threshold;
+    }
+
+    if !config.guardians.is_empty()
+        && (config.guardian_threshold == 0
+            || config.guardian_threshold as usize > config.guardians.len())
+    {
+        return Err(ContractError::InvalidGuardianThreshold {});
+    }
 
-        if let Some(interest_model) = interest_model {
-            config.interest_model = deps.api.addr_canonicalize(interest_model.as_str())?;
+    if let Some(claims_paused) = claims_paused {
+        config.claims_paused = claims_paused;
+    }
+
+    if let Some(rate_refresh_interval) = rate_refresh_interval {
+        config.rate_refresh_interval = rate_refresh_interval;
+    }
+
+    if let Some(min_anc_emission_rate) = min_anc_emission_rate {
+        config.min_anc_emission_rate = min_anc_emission_rate;
+    }
+
+    if let Some(low_liquidity_haircut) = low_liquidity_haircut {
+        if low_liquidity_haircut >= Decimal256::one() {
+            return Err(ContractError::InvalidLowLiquidityHaircut {});
         }
+
+        config.low_liquidity_haircut = Some(low_liquidity_haircut);
     }
 
-    if let Some(distribution_model) = distribution_model {
-        config.distribution_model = deps.api.addr_canonicalize(distribution_model.as_str())?;
+    if let Some(max_borrow_per_block) = max_borrow_per_block {
+        config.max_borrow_per_block = Some(max_borrow_per_block);
+    }
+
+    if let Some(locked_deposit_boost) = locked_deposit_boost {
+        config.locked_deposit_boost = locked_deposit_boost;
+    }
+
+    if let Some(burn_redeem_dust) = burn_redeem_dust {
+        config.burn_redeem_dust = burn_redeem_dust;
+    }
+
+    if let Some(strict_supply_check) = strict_supply_check {
+        config.strict_supply_check = strict_supply_check;
+    }
+
+    if let Some(blocks_per_year) = blocks_per_year {
+        if blocks_per_year == 0 {
+            return Err(ContractError::InvalidBlocksPerYear {});
+        }
+
+        config.blocks_per_year = blocks_per_year;
+    }
+
+    if let Some(max_total_deposits) = max_total_deposits {
+        config.max_total_deposits = Some(max_total_deposits);
+    }
+
+    if let Some(redeem_fast_path) = redeem_fast_path {
+        config.redeem_fast_path = redeem_fast_path;
+    }
+
+    if let Some(rounding_mode) = rounding_mode {
+        config.rounding_mode = rounding_mode;
+    }
+
+    if let Some(partial_fill_borrows) = partial_fill_borrows {
+        config.partial_fill_borrows = partial_fill_borrows;
+    }
+
+    if let Some(staking_contracts) = staking_contracts {
+        config.staking_contracts = staking_contracts
+            .iter()
+            .map(|staking_contract| deps.api.addr_canonicalize(staking_contract.as_str()))
+            .collect::<StdResult<Vec<CanonicalAddr>>>()?;
     }
 
-    if let Some(max_borrow_factor) = max_borrow_factor {
-        config.max_borrow_factor = max_borrow_factor;
+    if let Some(base_borrow_factor) = base_borrow_factor {
+        validate_max_borrow_factor(base_borrow_factor)?;
+        config.base_borrow_factor = base_borrow_factor;
     }
 
     store_config(deps.storage, &config)?;
     Ok(Response::new().add_attributes(vec![attr("action", "update_config")]))
 }
 
-pub fn execute_epoch_operations(
+pub fn queue_config_change(
     deps: DepsMut,
     env: Env,
     info: MessageInfo,
-    deposit_rate: Decimal256,
-    target_deposit_rate: Decimal256,
-    threshold_deposit_rate: Decimal256,
-    distributed_interest: Uint256,
+    change: ConfigChange,
+    eta: u64,
 ) -> Result<Response, ContractError> {
     let config: Config = read_config(deps.storage)?;
-    if config.overseer_contract != deps.api.addr_canonicalize(info.sender.as_str())? {
+
+    // permission check
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner_addr {
         return Err(ContractError::Unauthorized {});
     }
 
-    let mut state: State = read_state(deps.storage)?;
+    let min_eta = env.block.height + CONFIG_TIMELOCK;
+    if eta < min_eta {
+        return Err(ContractError::InvalidConfigChangeEta { min_eta });
+    }
 
-    // Compute interest and reward before updating anc_emission_rate
-    let aterra_supply = query_supply(
-        deps.as_ref(),
-        deps.api.addr_humanize(&config.aterra_contract)?,
-    )?;
-    let balance: Uint256 = query_balance(
-        deps.as_ref(),
-        deps.api.addr_humanize(&config.contract_addr)?,
-        config.stable_denom.to_string(),
-    )? - distributed_interest;
+    if let Some(max_borrow_factor) = change.max_borrow_factor {
+        validate_max_borrow_factor(max_borrow_factor)?;
+    }
 
-    let borrow_rate_res: BorrowRateResponse = query_borrow_rate(
-        deps.as_ref(),
-        deps.api.addr_humanize(&config.interest_model)?,
-        balance,
-        state.total_liabilities,
-        state.total_reserves,
-    )?;
+    let canonicalize = |addr: Option<String>| -> StdResult<Option<CanonicalAddr>> {
+        addr.map(|addr| {
+            deps.api
+                .addr_canonicalize(deps.api.addr_validate(&addr)?.as_str())
+        })
+        .transpose()
+    };
 
-    compute_interest_raw(
-        &mut state,
-        env.block.height,
-        balance,
-        aterra_supply,
-        borrow_rate_res.rate,
-        target_deposit_rate,
-    );
+    let mut pending_changes = read_pending_config_changes(deps.storage)?;
+    pending_changes.push(PendingConfigChange {
+        owner_addr: canonicalize(change.owner_addr)?,
+        interest_model: canonicalize(change.interest_model)?,
+        max_borrow_factor: change.max_borrow_factor,
+        eta,
+    });
+    store_pending_config_changes(deps.storage, &pending_changes)?;
 
-    // recompute prev_exchange_rate with distributed_interest
-    state.prev_exchange_rate =
-        compute_exchange_rate_raw(&state, aterra_supply, balance + distributed_interest);
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "queue_config_change"),
+        attr("eta", eta.to_string()),
+    ]))
+}
 
-    compute_reward(&mut state, env.block.height);
+/// Applies every queued change whose eta has passed, in queue order;
+/// the others stay queued
+pub fn apply_config_change(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+) -> Result<Response, ContractError> {
+    let mut config: Config = read_config(deps.storage)?;
 
-    // Compute total_reserves to fund collector contract
-    // Update total_reserves and send it to collector contract
-    // only when there is enough balance
-    let total_reserves = state.total_reserves * Uint256::one();
-    let messages: Vec<CosmosMsg> = if !total_reserves.is_zero() && balance > total_reserves {
-        state.total_reserves = state.total_reserves - Decimal256::from_uint256(total_reserves);
+    // permission check
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner_addr {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let (ready, pending): (Vec<PendingConfigChange>, Vec<PendingConfigChange>) =
+        read_pending_config_changes(deps.storage)?
+            .into_iter()
+            .partition(|change| change.eta <= env.block.height);
+
+    if ready.is_empty() {
+        return match pending.iter().map(|change| change.eta).min() {
+            Some(eta) => Err(ContractError::ConfigChangeTimelocked { eta }),
+            None => Err(ContractError::NoPendingConfigChange {}),
+        };
+    }
 
+    let mut interest_accrued = None;
+    if ready.iter().any(|change| change.interest_model.is_some()) {
+        let mut state: State = read_state(deps.storage)?;
+        interest_accrued =
+            compute_interest(deps.as_ref(), &config, &mut state, env.block.height, None)?;
+        // Settle rewards up to the swap as well, so the reward index
+        // does not carry the old rate environment past it
+        compute_reward(&mut state, env.block.height);
+        store_state(deps.storage, &state)?;
+    }
+
+    let applied = ready.len();
+    for change in ready {
+        if let Some(owner_addr) = change.owner_addr {
+            config.owner_addr = owner_addr;
+        }
+
+        if let Some(interest_model) = change.interest_model {
+            config.interest_model = interest_model;
+        }
+
+        if let Some(max_borrow_factor) = change.max_borrow_factor {
+            config.max_borrow_factor = max_borrow_factor;
+        }
+    }
+
+    store_config(deps.storage, &config)?;
+    store_pending_config_changes(deps.storage, &pending)?;
+
+    Ok(Response::new()
+        .add_events(interest_accrued)
+        .add_attributes(vec![
+            attr("action", "apply_config_change"),
+            attr("applied", applied.to_string()),
+        ]))
+}
+
+pub fn sweep_token(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    denom: String,
+    recipient: Addr,
+) -> Result<Response, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+
+    // permission check
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner_addr {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    if denom == config.stable_denom {
+        return Err(ContractError::CannotSweep(denom));
+    }
+
+    let amount = query_balance(deps.as_ref(), env.contract.address, denom.to_string())?;
+    let messages: Vec<CosmosMsg> = if !amount.is_zero() {
         vec![CosmosMsg::Bank(BankMsg::Send {
-            to_address: deps
-                .api
-                .addr_humanize(&config.collector_contract)?
-                .to_string(),
+            to_address: recipient.to_string(),
             amount: vec![deduct_tax(
                 deps.as_ref(),
                 Coin {
-                    denom: config.stable_denom,
-                    amount: total_reserves.into(),
+                    denom: denom.to_string(),
+                    amount: amount.into(),
                 },
             )?],
         })]
@@ -400,31 +1036,480 @@ pub fn execute_epoch_operations(
         vec![]
     };
 
-    // Query updated anc_emission_rate
-    state.anc_emission_rate = query_anc_emission_rate(
-        deps.as_ref(),
-        deps.api.addr_humanize(&config.distribution_model)?,
-        deposit_rate,
-        target_deposit_rate,
-        threshold_deposit_rate,
-        state.anc_emission_rate,
-    )?
-    .emission_rate;
-
-    store_state(deps.storage, &state)?;
+    Ok(Response::new().add_messages(messages).add_attributes(vec![
+        attr("action", "sweep_token"),
+        attr("denom", denom),
+        attr("recipient", recipient),
+        attr("amount", amount),
+    ]))
+}
+
+pub fn sweep_cw20_token(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    contract_addr: Addr,
+    recipient: Addr,
+) -> Result<Response, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+
+    // permission check
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner_addr {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    if deps.api.addr_canonicalize(contract_addr.as_str())? == config.aterra_contract
+        || (config.stable_cw20 && contract_addr.as_str() == config.stable_denom)
+    {
+        return Err(ContractError::CannotSweep(contract_addr.to_string()));
+    }
+
+    let amount = query_token_balance(deps.as_ref(), contract_addr.clone(), env.contract.address)?;
+    let messages: Vec<CosmosMsg> = if !amount.is_zero() {
+        vec![CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: contract_addr.to_string(),
+            funds: vec![],
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: recipient.to_string(),
+                amount: amount.into(),
+            })?,
+        })]
+    } else {
+        vec![]
+    };
 
     Ok(Response::new().add_messages(messages).add_attributes(vec![
+        attr("action", "sweep_cw20_token"),
+        attr("token", contract_addr),
+        attr("recipient", recipient),
+        attr("amount", amount),
+    ]))
+}
+
+pub fn set_last_interest_updated(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    block_height: u64,
+) -> Result<Response, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+
+    // permission check
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner_addr {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let mut state: State = read_state(deps.storage)?;
+    if block_height > env.block.height || block_height >= state.last_interest_updated {
+        return Err(ContractError::InvalidLastInterestUpdated {});
+    }
+
+    let prev_last_interest_updated = state.last_interest_updated;
+    state.last_interest_updated = block_height;
+    store_state(deps.storage, &state)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "set_last_interest_updated"),
+        attr(
+            "warning",
+            "last_interest_updated overridden by owner for recovery",
+        ),
+        attr(
+            "prev_last_interest_updated",
+            prev_last_interest_updated.to_string(),
+        ),
+        attr("last_interest_updated", block_height.to_string()),
+    ]))
+}
+
+pub fn reset_peak_utilization(deps: DepsMut, info: MessageInfo) -> Result<Response, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+
+    // permission check
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner_addr {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let mut state: State = read_state(deps.storage)?;
+    let prev_peak_utilization = state.peak_utilization;
+    state.peak_utilization = Decimal256::zero();
+    store_state(deps.storage, &state)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "reset_peak_utilization"),
+        attr("prev_peak_utilization", prev_peak_utilization.to_string()),
+    ]))
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn execute_epoch_operations(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    deposit_rate: Decimal256,
+    target_deposit_rate: Decimal256,
+    threshold_deposit_rate: Decimal256,
+    distributed_interest: Uint256,
+    reserve_only: bool,
+) -> Result<Response, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+    let sender_raw = deps.api.addr_canonicalize(info.sender.as_str())?;
+    if config.overseer_contract != sender_raw && config.epoch_caller != Some(sender_raw) {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let mut state: State = read_state(deps.storage)?;
+
+    // Compute interest and reward before updating anc_emission_rate
+    let snapshot = MarketSnapshot::load(deps.as_ref(), &config, Some(distributed_interest))?;
+    let (balance, aterra_supply) = (snapshot.balance, snapshot.aterra_supply);
+
+    // The tracked supply is resynced with the token at every accrual and moved
+    // by the market's own mints and burns in between, so a supply above it
+    // means aterra was minted outside the market. A lower one is only warned
+    // about, as holders may burn their own aterra
+    let supply_mismatch = aterra_supply != state.prev_aterra_supply;
+    if aterra_supply > state.prev_aterra_supply && config.strict_supply_check {
+        return Err(ContractError::SupplyMismatch {
+            expected: state.prev_aterra_supply.into(),
+            actual: aterra_supply.into(),
+        });
+    }
+    let tracked_aterra_supply = state.prev_aterra_supply;
+
+    let (borrow_rate, used_fallback) = cached_market_borrow_rate(
+        deps.as_ref(),
+        &config,
+        &mut state,
+        env.block.height,
+        balance,
+    )?;
+
+    let interest_accrued = mark_fallback(
+        compute_interest_raw(
+            &mut state,
+            env.block.height,
+            balance,
+            aterra_supply,
+            borrow_rate,
+            target_deposit_rate,
+            &config.rounding_mode,
+        ),
+        used_fallback,
+    );
+
+    // recompute prev_exchange_rate with distributed_interest
+    state.prev_exchange_rate =
+        compute_exchange_rate_raw(&state, aterra_supply, balance + distributed_interest);
+
+    compute_reward(&mut state, env.block.height);
+    store_reward_index_snapshot(
+        deps.storage,
+        &RewardIndexSnapshot {
+            block_height: env.block.height,
+            global_reward_index: state.global_reward_index,
+            global_interest_index: state.global_interest_index,
+        },
+    )?;
+    store_liability_snapshot(
+        deps.storage,
+        &LiabilitySnapshot {
+            block_height: env.block.height,
+            total_liabilities: state.total_liabilities,
+        },
+    )?;
+
+    let (total_reserves, messages) =
+        compute_reserves_transfer(deps.as_ref(), &config, &mut state, balance)?;
+
+    // Query updated anc_emission_rate, clamped up to the floor
+    if !reserve_only {
+        state.anc_emission_rate = std::cmp::max(
+            query_anc_emission_rate(
+                deps.as_ref(),
+                deps.api.addr_humanize(&config.distribution_model)?,
+                deposit_rate,
+                target_deposit_rate,
+                threshold_deposit_rate,
+                state.anc_emission_rate,
+            )?
+            .emission_rate,
+            config.min_anc_emission_rate,
+        );
+    }
+
+    store_state(deps.storage, &state)?;
+
+    let mut attributes = vec![
         attr("action", "execute_epoch_operations"),
         attr("total_reserves", total_reserves),
         attr("anc_emission_rate", state.anc_emission_rate.to_string()),
+    ];
+
+    if reserve_only {
+        attributes.push(attr("reserve_only", "true"));
+    }
+
+    if supply_mismatch {
+        attributes.push(attr("supply_mismatch", aterra_supply));
+        attributes.push(attr("tracked_aterra_supply", tracked_aterra_supply));
+    }
+
+    // Signal how the reserves stand against the target
+    if let Some(reserve_target) = config.reserve_target {
+        if total_reserves > reserve_target {
+            attributes.push(attr(
+                "reserve_above_target",
+                total_reserves - reserve_target,
+            ));
+        } else if total_reserves < reserve_target {
+            attributes.push(attr(
+                "reserve_below_target",
+                reserve_target - total_reserves,
+            ));
+        }
+    }
+
+    Ok(Response::new()
+        .add_events(interest_accrued)
+        .add_submessages(messages)
+        .add_attributes(attributes))
+}
+
+pub fn transfer_reserves(deps: DepsMut, env: Env) -> Result<Response, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+    let mut state: State = read_state(deps.storage)?;
+
+    let interest_accrued =
+        compute_interest(deps.as_ref(), &config, &mut state, env.block.height, None)?;
+
+    let balance = query_stable_balance(deps.as_ref(), &config)?;
+    let (total_reserves, messages) =
+        compute_reserves_transfer(deps.as_ref(), &config, &mut state, balance)?;
+
+    store_state(deps.storage, &state)?;
+
+    Ok(Response::new()
+        .add_events(interest_accrued)
+        .add_submessages(messages)
+        .add_attributes(vec![
+            attr("action", "transfer_reserves"),
+            attr("total_reserves", total_reserves),
+        ]))
+}
+
+// Compute total_reserves to fund collector contract
+// Update total_reserves and send it to collector contract
+// only when there is enough balance; with a reserve target and
+// an excess recipient, the excess above target goes to the latter.
+// The collector transfer replies, so a failed send restores its
+// reserves instead of reverting the whole epoch
+fn compute_reserves_transfer(
+    deps: Deps,
+    config: &Config,
+    state: &mut State,
+    balance: Uint256,
+) -> StdResult<(Uint256, Vec<SubMsg>)> {
+    let total_reserves = state.total_reserves * Uint256::one();
+    if total_reserves.is_zero() {
+        return Ok((total_reserves, vec![]));
+    }
+
+    // Reserves the balance cannot cover keep accumulating, up to
+    // max_reserves when the balance covers the overflow
+    if balance <= total_reserves {
+        let mut messages: Vec<SubMsg> = vec![];
+        if let (Some(max_reserves), Some(reserve_excess_recipient)) =
+            (config.max_reserves, &config.reserve_excess_recipient)
+        {
+            if total_reserves > max_reserves && balance > total_reserves - max_reserves {
+                let overflow = total_reserves - max_reserves;
+                state.total_reserves = state.total_reserves - Decimal256::from_uint256(overflow);
+                messages.push(SubMsg::new(stable_transfer_msg(
+                    deps,
+                    config,
+                    deps.api
+                        .addr_humanize(reserve_excess_recipient)?
+                        .to_string(),
+                    overflow,
+                )?));
+            }
+        }
+
+        return Ok((total_reserves, messages));
+    }
+
+    state.total_reserves = state.total_reserves - Decimal256::from_uint256(total_reserves);
+
+    // (recipient, amount) of the reserves above target, when routed
+    let excess = match (config.reserve_target, &config.reserve_excess_recipient) {
+        (Some(reserve_target), Some(reserve_excess_recipient))
+            if total_reserves > reserve_target =>
+        {
+            Some((reserve_excess_recipient, total_reserves - reserve_target))
+        }
+        _ => None,
+    };
+    let collector_amount = match excess {
+        Some((_, excess_amount)) => total_reserves - excess_amount,
+        None => total_reserves,
+    };
+
+    let mut messages: Vec<SubMsg> = vec![];
+    if !collector_amount.is_zero() {
+        state.pending_reserve_transfer = collector_amount;
+        messages.push(SubMsg::reply_always(
+            stable_transfer_msg(
+                deps,
+                config,
+                deps.api
+                    .addr_humanize(&config.collector_contract)?
+                    .to_string(),
+                collector_amount,
+            )?,
+            RESERVE_TRANSFER_REPLY_ID,
+        ));
+    }
+    if let Some((reserve_excess_recipient, excess_amount)) = excess {
+        messages.push(SubMsg::new(stable_transfer_msg(
+            deps,
+            config,
+            deps.api
+                .addr_humanize(reserve_excess_recipient)?
+                .to_string(),
+            excess_amount,
+        )?));
+    }
+
+    Ok((total_reserves, messages))
+}
+
+/// Settle the collector transfer of compute_reserves_transfer;
+/// a failed one leaves its reserves for the next transfer to retry,
+/// so only confirmed ones enter the transfer history
+fn reserve_transfer_reply(
+    deps: DepsMut,
+    env: Env,
+    failed: bool,
+) -> Result<Response, ContractError> {
+    let mut state: State = read_state(deps.storage)?;
+    let amount = state.pending_reserve_transfer;
+    if !failed {
+        state.pending_reserve_transfer = Uint256::zero();
+        state.cumulative_reserves_collected += amount;
+        store_state(deps.storage, &state)?;
+        store_reserve_transfer(
+            deps.storage,
+            &ReserveTransfer {
+                block_height: env.block.height,
+                amount,
+            },
+        )?;
+        return Ok(Response::new().add_attributes(vec![
+            attr("action", "reserve_transfer_confirmed"),
+            attr("collected_reserves", amount),
+        ]));
+    }
+
+    state.total_reserves += Decimal256::from_uint256(amount);
+
+    // The stable never left, so a cw20 stable's tracked balance keeps it
+    let config: Config = read_config(deps.storage)?;
+    if config.stable_cw20 {
+        state.stable_cw20_balance += amount;
+    }
+    store_state(deps.storage, &state)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "reserve_transfer_failed"),
+        attr("restored_reserves", amount),
     ]))
 }
 
+/// The aterra name and symbol are derived by slicing the denom,
+/// which needs a leading `u` and at least one more ascii character
+/// between it and the last one, e.g. uusd => Anchor Terra USD, aUST
+fn validate_stable_denom(stable_denom: &str) -> Result<(), ContractError> {
+    if stable_denom.len() < 3
+        || !stable_denom.starts_with('u')
+        || !stable_denom.chars().all(|c| c.is_ascii_alphanumeric())
+    {
+        return Err(ContractError::InvalidStableDenom(stable_denom.to_string()));
+    }
+
+    Ok(())
+}
+
+/// Same constraints the token contract puts on its name:
+/// 3 to 50 characters
+fn validate_aterra_name(name: &str) -> Result<(), ContractError> {
+    if name.len() < 3 || name.len() > 50 {
+        return Err(ContractError::InvalidAterraName(name.to_string()));
+    }
+
+    Ok(())
+}
+
+/// Same constraints the token contract puts on its symbol:
+/// 3 to 12 ascii letters or dashes
+fn validate_aterra_symbol(symbol: &str) -> Result<(), ContractError> {
+    if symbol.len() < 3
+        || symbol.len() > 12
+        || !symbol.chars().all(|c| c.is_ascii_alphabetic() || c == '-')
+    {
+        return Err(ContractError::InvalidAterraSymbol(symbol.to_string()));
+    }
+
+    Ok(())
+}
+
+fn validate_max_borrow_factor(max_borrow_factor: Decimal256) -> Result<(), ContractError> {
+    if max_borrow_factor > Decimal256::one() {
+        return Err(ContractError::InvalidMaxBorrowFactor {});
+    }
+
+    Ok(())
+}
+
+/// The fee is taken out of the recovered amount, so it can never be more than all of it
+pub fn validate_liquidation_fee_rate(
+    liquidation_fee_rate: Decimal256,
+) -> Result<(), ContractError> {
+    if liquidation_fee_rate > Decimal256::one() {
+        return Err(ContractError::InvalidLiquidationFeeRate {});
+    }
+
+    Ok(())
+}
+
+/// Tiers must ascend in tenure and never lower the multiplier,
+/// so the last one caps the boost
+fn validate_reward_tenure_tiers(tiers: &[RewardTenureTier]) -> Result<(), ContractError> {
+    let mut prev: Option<&RewardTenureTier> = None;
+    for tier in tiers {
+        let ascending = match prev {
+            Some(prev) => tier.min_tenure > prev.min_tenure && tier.multiplier >= prev.multiplier,
+            None => tier.multiplier >= Decimal256::one(),
+        };
+        if !ascending {
+            return Err(ContractError::InvalidRewardTenureTiers {});
+        }
+
+        prev = Some(tier);
+    }
+
+    Ok(())
+}
+
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
     match msg {
         QueryMsg::Config {} => to_binary(&query_config(deps)?),
+        QueryMsg::ConfigAudit {} => to_binary(&query_config_audit(deps)?),
         QueryMsg::State { block_height } => to_binary(&query_state(deps, env, block_height)?),
+        QueryMsg::HistoricalState { block_height } => {
+            to_binary(&query_historical_state(deps, block_height)?)
+        }
         QueryMsg::EpochState {
             block_height,
             distributed_interest,
@@ -433,6 +1518,9 @@ pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
             block_height,
             distributed_interest,
         )?),
+        QueryMsg::ConfigAndState { block_height } => {
+            to_binary(&query_config_and_state(deps, env, block_height)?)
+        }
         QueryMsg::BorrowerInfo {
             borrower,
             block_height,
@@ -447,6 +1535,119 @@ pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
             optional_addr_validate(deps.api, start_after)?,
             limit,
         )?),
+        QueryMsg::BorrowerInfosByList {
+            borrowers,
+            block_height,
+        } => to_binary(&query_borrower_infos_by_list(
+            deps,
+            env,
+            borrowers
+                .iter()
+                .map(|borrower| deps.api.addr_validate(borrower))
+                .collect::<StdResult<Vec<Addr>>>()?,
+            block_height,
+        )?),
+        QueryMsg::ReferralVolume { referrer } => to_binary(&query_referral_volume(
+            deps,
+            deps.api.addr_validate(&referrer)?,
+        )?),
+        QueryMsg::BorrowerHealth { borrower } => to_binary(&query_borrower_health(
+            deps,
+            env,
+            deps.api.addr_validate(&borrower)?,
+        )?),
+        QueryMsg::ClaimableReward { borrower } => to_binary(&query_claimable_reward(
+            deps,
+            env,
+            deps.api.addr_validate(&borrower)?,
+        )?),
+        QueryMsg::IsLiquidatable { borrower } => to_binary(&query_is_liquidatable(
+            deps,
+            env,
+            deps.api.addr_validate(&borrower)?,
+        )?),
+        QueryMsg::BorrowerUtilization { borrower } => to_binary(&query_borrower_utilization(
+            deps,
+            env,
+            deps.api.addr_validate(&borrower)?,
+        )?),
+        QueryMsg::RewardIndexHistory { limit } => {
+            to_binary(&query_reward_index_history(deps, limit)?)
+        }
+        QueryMsg::LiabilityHistory { limit } => to_binary(&query_liability_history(deps, limit)?),
+        QueryMsg::ReserveTransferHistory { limit } => {
+            to_binary(&query_reserve_transfer_history(deps, limit)?)
+        }
+        QueryMsg::TotalDeposits {} => to_binary(&query_total_deposits(deps, env)?),
+        QueryMsg::RemainingDepositCapacity {} => {
+            to_binary(&query_remaining_deposit_capacity(deps, env)?)
+        }
+        QueryMsg::RewardState { block_height } => {
+            to_binary(&query_reward_state(deps, env, block_height)?)
+        }
+        QueryMsg::TaxAmount { amount } => to_binary(&query_tax_amount(deps, amount)?),
+        QueryMsg::PendingConfigChanges {} => to_binary(&query_pending_config_changes(deps)?),
+        QueryMsg::ProjectedReserves { block_height } => {
+            to_binary(&query_projected_reserves(deps, env, block_height)?)
+        }
+        QueryMsg::RateSpread { block_height } => {
+            to_binary(&query_rate_spread(deps, env, block_height)?)
+        }
+        QueryMsg::NetDepositRate { block_height } => {
+            to_binary(&query_net_deposit_rate(deps, env, block_height)?)
+        }
+        QueryMsg::AterraToUnderlying {
+            aterra_amount,
+            block_height,
+        } => to_binary(&query_aterra_to_underlying(
+            deps,
+            env,
+            aterra_amount,
+            block_height,
+        )?),
+        QueryMsg::IntegrityCheck {} => to_binary(&query_integrity_check(deps, env)?),
+        QueryMsg::MarketSummary { block_height } => {
+            to_binary(&query_market_summary(deps, env, block_height)?)
+        }
+        QueryMsg::Proposal { id } => to_binary(&query_proposal(deps, id)?),
+        QueryMsg::Freshness {} => to_binary(&query_freshness(deps, env)?),
+        QueryMsg::LockedDeposit { lock_id } => to_binary(&query_locked_deposit(deps, lock_id)?),
+        QueryMsg::TvlInReference {
+            oracle,
+            reference_denom,
+        } => to_binary(&query_tvl_in_reference(
+            deps,
+            env,
+            optional_addr_validate(deps.api, oracle)?,
+            reference_denom,
+        )?),
+        QueryMsg::UnderlyingToAterra {
+            underlying_amount,
+            block_height,
+        } => to_binary(&query_underlying_to_aterra(
+            deps,
+            env,
+            underlying_amount,
+            block_height,
+        )?),
+        QueryMsg::Rates {
+            block_height,
+            compounding,
+        } => to_binary(&query_rates(deps, env, block_height, compounding)?),
+        QueryMsg::PayoffAmount {
+            borrower,
+            block_height,
+        } => to_binary(&query_payoff_amount(
+            deps,
+            env,
+            deps.api.addr_validate(&borrower)?,
+            block_height,
+        )?),
+        QueryMsg::BorrowerRate { borrower } => to_binary(&query_borrower_rate(
+            deps,
+            env,
+            deps.api.addr_validate(&borrower)?,
+        )?),
     }
 }
 
@@ -474,9 +1675,152 @@ pub fn query_config(deps: Deps) -> StdResult<ConfigResponse> {
             .to_string(),
         stable_denom: config.stable_denom,
         max_borrow_factor: config.max_borrow_factor,
+        borrow_grace_period: config.borrow_grace_period,
+        epoch_caller: config
+            .epoch_caller
+            .map(|epoch_caller| deps.api.addr_humanize(&epoch_caller))
+            .transpose()?
+            .map(|epoch_caller| epoch_caller.to_string()),
+        borrow_cooldown: config.borrow_cooldown,
+        redeem_lockup: config.redeem_lockup,
+        stable_cw20: config.stable_cw20,
+        tax_cap_override: config.tax_cap_override,
+        min_liquidity_ratio: config.min_liquidity_ratio,
+        liquidation_fee_rate: config.liquidation_fee_rate,
+        max_borrower_infos_limit: config.max_borrower_infos_limit,
+        fallback_interest_model: config
+            .fallback_interest_model
+            .map(|fallback_interest_model| deps.api.addr_humanize(&fallback_interest_model))
+            .transpose()?
+            .map(|fallback_interest_model| fallback_interest_model.to_string()),
+        fallback_borrow_rate: config.fallback_borrow_rate,
+        utilization_sensitivity: config.utilization_sensitivity,
+        reward_tenure_tiers: config.reward_tenure_tiers,
+        reserve_target: config.reserve_target,
+        reserve_excess_recipient: config
+            .reserve_excess_recipient
+            .map(|reserve_excess_recipient| deps.api.addr_humanize(&reserve_excess_recipient))
+            .transpose()?
+            .map(|reserve_excess_recipient| reserve_excess_recipient.to_string()),
+        max_reserves: config.max_reserves,
+        max_borrow_per_tx: config.max_borrow_per_tx,
+        guardians: config
+            .guardians
+            .iter()
+            .map(|guardian| Ok(deps.api.addr_humanize(guardian)?.to_string()))
+            .collect::<StdResult<Vec<String>>>()?,
+        guardian_threshold: config.guardian_threshold,
+        claims_paused: config.claims_paused,
+        rate_refresh_interval: config.rate_refresh_interval,
+        min_anc_emission_rate: config.min_anc_emission_rate,
+        low_liquidity_haircut: config.low_liquidity_haircut,
+        max_borrow_per_block: config.max_borrow_per_block,
+        locked_deposit_boost: config.locked_deposit_boost,
+        burn_redeem_dust: config.burn_redeem_dust,
+        strict_supply_check: config.strict_supply_check,
+        blocks_per_year: config.blocks_per_year,
+        max_total_deposits: config.max_total_deposits,
+        redeem_fast_path: config.redeem_fast_path,
+        rounding_mode: config.rounding_mode,
+        partial_fill_borrows: config.partial_fill_borrows,
+        staking_contracts: config
+            .staking_contracts
+            .iter()
+            .map(|staking_contract| Ok(deps.api.addr_humanize(staking_contract)?.to_string()))
+            .collect::<StdResult<Vec<String>>>()?,
+        base_borrow_factor: config.base_borrow_factor,
     })
 }
 
+/// Compares the config against the values `instantiate` sets,
+/// leaving out the addresses and parameters given at instantiation
+pub fn query_config_audit(deps: Deps) -> StdResult<ConfigAuditResponse> {
+    let config: Config = read_config(deps.storage)?;
+
+    let fields = [
+        ("borrow_grace_period", config.borrow_grace_period != 0),
+        ("epoch_caller", config.epoch_caller.is_some()),
+        ("borrow_cooldown", config.borrow_cooldown != 0),
+        ("redeem_lockup", config.redeem_lockup != 0),
+        ("tax_cap_override", config.tax_cap_override.is_some()),
+        ("min_liquidity_ratio", !config.min_liquidity_ratio.is_zero()),
+        (
+            "liquidation_fee_rate",
+            !config.liquidation_fee_rate.is_zero(),
+        ),
+        (
+            "max_borrower_infos_limit",
+            config.max_borrower_infos_limit != MAX_LIMIT,
+        ),
+        (
+            "fallback_interest_model",
+            config.fallback_interest_model.is_some(),
+        ),
+        (
+            "fallback_borrow_rate",
+            config.fallback_borrow_rate.is_some(),
+        ),
+        (
+            "utilization_sensitivity",
+            !config.utilization_sensitivity.is_zero(),
+        ),
+        (
+            "reward_tenure_tiers",
+            !config.reward_tenure_tiers.is_empty(),
+        ),
+        ("reserve_target", config.reserve_target.is_some()),
+        (
+            "reserve_excess_recipient",
+            config.reserve_excess_recipient.is_some(),
+        ),
+        ("max_reserves", config.max_reserves.is_some()),
+        ("max_borrow_per_tx", config.max_borrow_per_tx.is_some()),
+        ("guardians", !config.guardians.is_empty()),
+        ("guardian_threshold", config.guardian_threshold != 0),
+        ("claims_paused", config.claims_paused),
+        ("rate_refresh_interval", config.rate_refresh_interval != 0),
+        (
+            "min_anc_emission_rate",
+            !config.min_anc_emission_rate.is_zero(),
+        ),
+        (
+            "low_liquidity_haircut",
+            config.low_liquidity_haircut.is_some(),
+        ),
+        (
+            "max_borrow_per_block",
+            config.max_borrow_per_block.is_some(),
+        ),
+        (
+            "locked_deposit_boost",
+            !config.locked_deposit_boost.is_zero(),
+        ),
+        ("burn_redeem_dust", config.burn_redeem_dust),
+        ("strict_supply_check", config.strict_supply_check),
+        ("blocks_per_year", config.blocks_per_year != BLOCKS_PER_YEAR),
+        ("max_total_deposits", config.max_total_deposits.is_some()),
+        ("redeem_fast_path", config.redeem_fast_path),
+        ("rounding_mode", config.rounding_mode != RoundingMode::Floor),
+        ("partial_fill_borrows", config.partial_fill_borrows),
+        ("staking_contracts", !config.staking_contracts.is_empty()),
+        (
+            "base_borrow_factor",
+            config.base_borrow_factor != config.max_borrow_factor,
+        ),
+    ];
+
+    Ok(ConfigAuditResponse {
+        deviations: fields
+            .iter()
+            .filter(|(_, deviates)| *deviates)
+            .map(|(field, _)| field.to_string())
+            .collect(),
+    })
+}
+
+/// State projected to `block_height`. Earlier heights the reward index
+/// history covers answer with the historical indices of
+/// query_historical_state; every other field is as of the latest update
 pub fn query_state(deps: Deps, env: Env, block_height: Option<u64>) -> StdResult<StateResponse> {
     let mut state: State = read_state(deps.storage)?;
 
@@ -486,6 +1830,14 @@ pub fn query_state(deps: Deps, env: Env, block_height: Option<u64>) -> StdResult
         env.block.height
     };
 
+    if block_height < state.last_interest_updated || block_height < state.last_reward_updated {
+        if let Ok(historical) = query_historical_state(deps, block_height) {
+            state.global_interest_index = historical.global_interest_index;
+            state.global_reward_index = historical.global_reward_index;
+            return Ok(state_response(state));
+        }
+    }
+
     if block_height < state.last_interest_updated {
         return Err(StdError::generic_err(
             "block_height must bigger than last_interest_updated",
@@ -506,8 +1858,13 @@ pub fn query_state(deps: Deps, env: Env, block_height: Option<u64>) -> StdResult
     // Compute reward rate with given block height
     compute_reward(&mut state, block_height);
 
-    Ok(StateResponse {
+    Ok(state_response(state))
+}
+
+fn state_response(state: State) -> StateResponse {
+    StateResponse {
         total_liabilities: state.total_liabilities,
+        total_fixed_liabilities: state.total_fixed_liabilities,
         total_reserves: state.total_reserves,
         last_interest_updated: state.last_interest_updated,
         last_reward_updated: state.last_reward_updated,
@@ -516,6 +1873,131 @@ pub fn query_state(deps: Deps, env: Env, block_height: Option<u64>) -> StdResult
         anc_emission_rate: state.anc_emission_rate,
         prev_aterra_supply: state.prev_aterra_supply,
         prev_exchange_rate: state.prev_exchange_rate,
+        peak_utilization: state.peak_utilization,
+        cumulative_reserves_collected: state.cumulative_reserves_collected,
+    }
+}
+
+/// Accrual ages are counted in blocks, the market's unit of time;
+/// a height set in the future reads as fresh
+pub fn query_freshness(deps: Deps, env: Env) -> StdResult<FreshnessResponse> {
+    let state: State = read_state(deps.storage)?;
+    Ok(FreshnessResponse {
+        interest_age_blocks: env.block.height.saturating_sub(state.last_interest_updated),
+        reward_age_blocks: env.block.height.saturating_sub(state.last_reward_updated),
+    })
+}
+
+/// Indices at a past `block_height`, interpolated between the reward index
+/// snapshot at or before it and the next one, or the current state.
+/// The snapshots record nothing else, so nothing else is returned.
+/// Accrual grows the interest index linearly, so the interpolation is exact
+/// unless accruals between the two points compounded it, when it slightly
+/// overstates the index
+pub fn query_historical_state(deps: Deps, block_height: u64) -> StdResult<HistoricalStateResponse> {
+    let state: State = read_state(deps.storage)?;
+    if block_height > state.last_interest_updated.min(state.last_reward_updated) {
+        return Err(StdError::generic_err(
+            "block_height must not be after the latest update; query State instead",
+        ));
+    }
+
+    // newest first
+    let history = read_reward_index_history(deps.storage, Some(HISTORY_CAPACITY as u32))?;
+    let before = match history.iter().position(|s| s.block_height <= block_height) {
+        Some(before) if !history[before].global_interest_index.is_zero() => before,
+        _ => {
+            return Err(StdError::generic_err(
+                "no reward index snapshot covers block_height",
+            ))
+        }
+    };
+
+    let lower = &history[before];
+    let upper = match before {
+        0 => RewardIndexSnapshot {
+            block_height: state.last_interest_updated.min(state.last_reward_updated),
+            global_reward_index: state.global_reward_index,
+            global_interest_index: state.global_interest_index,
+        },
+        _ => history[before - 1].clone(),
+    };
+
+    let interpolate = |lower_index: Decimal256, upper_index: Decimal256| {
+        if block_height >= upper.block_height {
+            return upper_index;
+        }
+        if block_height == lower.block_height || upper_index <= lower_index {
+            return lower_index;
+        }
+
+        lower_index
+            + (upper_index - lower_index)
+                * Decimal256::from_ratio(
+                    block_height - lower.block_height,
+                    upper.block_height - lower.block_height,
+                )
+    };
+
+    Ok(HistoricalStateResponse {
+        block_height,
+        global_interest_index: interpolate(
+            lower.global_interest_index,
+            upper.global_interest_index,
+        ),
+        global_reward_index: interpolate(lower.global_reward_index, upper.global_reward_index),
+    })
+}
+
+pub fn query_reward_state(
+    deps: Deps,
+    env: Env,
+    block_height: Option<u64>,
+) -> StdResult<RewardStateResponse> {
+    let state = query_state(deps, env, block_height)?;
+
+    Ok(RewardStateResponse {
+        global_reward_index: state.global_reward_index,
+        anc_emission_rate: state.anc_emission_rate,
+        last_reward_updated: state.last_reward_updated,
+    })
+}
+
+pub fn query_config_and_state(
+    deps: Deps,
+    env: Env,
+    block_height: Option<u64>,
+) -> StdResult<ConfigAndStateResponse> {
+    Ok(ConfigAndStateResponse {
+        config: query_config(deps)?,
+        state: query_state(deps, env, block_height)?,
+    })
+}
+
+pub fn query_reward_index_history(
+    deps: Deps,
+    limit: Option<u32>,
+) -> StdResult<RewardIndexHistoryResponse> {
+    Ok(RewardIndexHistoryResponse {
+        history: read_reward_index_history(deps.storage, limit)?,
+    })
+}
+
+pub fn query_liability_history(
+    deps: Deps,
+    limit: Option<u32>,
+) -> StdResult<LiabilityHistoryResponse> {
+    Ok(LiabilityHistoryResponse {
+        history: read_liability_history(deps.storage, limit)?,
+    })
+}
+
+pub fn query_reserve_transfer_history(
+    deps: Deps,
+    limit: Option<u32>,
+) -> StdResult<ReserveTransferHistoryResponse> {
+    Ok(ReserveTransferHistoryResponse {
+        history: read_reserve_transfer_history(deps.storage, limit)?,
     })
 }
 
@@ -529,11 +2011,7 @@ pub fn query_epoch_state(
 
     let distributed_interest = distributed_interest.unwrap_or_else(Uint256::zero);
     let aterra_supply = query_supply(deps, deps.api.addr_humanize(&config.aterra_contract)?)?;
-    let balance = query_balance(
-        deps,
-        deps.api.addr_humanize(&config.contract_addr)?,
-        config.stable_denom.to_string(),
-    )? - distributed_interest;
+    let balance = query_stable_balance(deps, &config)? - distributed_interest;
 
     if let Some(block_height) = block_height {
         if block_height < state.last_interest_updated {
@@ -542,13 +2020,8 @@ pub fn query_epoch_state(
             ));
         }
 
-        let borrow_rate_res: BorrowRateResponse = query_borrow_rate(
-            deps,
-            deps.api.addr_humanize(&config.interest_model)?,
-            balance,
-            state.total_liabilities,
-            state.total_reserves,
-        )?;
+        let (borrow_rate, _) =
+            cached_market_borrow_rate(deps, &config, &mut state, block_height, balance)?;
 
         let target_deposit_rate: Decimal256 =
             query_target_deposit_rate(deps, deps.api.addr_humanize(&config.overseer_contract)?)?;
@@ -559,8 +2032,9 @@ pub fn query_epoch_state(
             block_height,
             balance,
             aterra_supply,
-            borrow_rate_res.rate,
+            borrow_rate,
             target_deposit_rate,
+            &config.rounding_mode,
         );
     }
 
@@ -574,3 +2048,90 @@ pub fn query_epoch_state(
         aterra_supply,
     })
 }
+
+/// Runs the epoch reserve computation on the projected state,
+/// assuming the overseer distributes no interest at the epoch
+pub fn query_projected_reserves(
+    deps: Deps,
+    env: Env,
+    block_height: Option<u64>,
+) -> StdResult<ProjectedReservesResponse> {
+    let config: Config = read_config(deps.storage)?;
+    let mut state: State = read_state(deps.storage)?;
+
+    let block_height = block_height.unwrap_or(env.block.height);
+    if block_height < state.last_interest_updated {
+        return Err(StdError::generic_err(
+            "block_height must bigger than last_interest_updated",
+        ));
+    }
+
+    compute_interest(deps, &config, &mut state, block_height, None)?;
+
+    // Reserves sent out are the whole amounts taken off total_reserves
+    let accrued_reserves = state.total_reserves;
+    let balance = query_stable_balance(deps, &config)?;
+    compute_reserves_transfer(deps, &config, &mut state, balance)?;
+
+    Ok(ProjectedReservesResponse {
+        reserves: (accrued_reserves - state.total_reserves) * Uint256::one(),
+    })
+}
+
+pub fn query_market_summary(
+    deps: Deps,
+    env: Env,
+    block_height: Option<u64>,
+) -> StdResult<MarketSummaryResponse> {
+    let mut env = env;
+    env.block.height = block_height.unwrap_or(env.block.height);
+
+    let state = query_state(deps, env.clone(), None)?;
+    let total_deposits = query_total_deposits(deps, env.clone())?;
+    let rates = query_rates(deps, env, None, Compounding::Simple)?;
+
+    let config: Config = read_config(deps.storage)?;
+    let stable_balance = query_stable_balance(deps, &config)?;
+
+    let deposits = total_deposits.user_deposits + total_deposits.protocol_deposits;
+    let utilization = if deposits.is_zero() {
+        Decimal256::zero()
+    } else {
+        (state.total_liabilities + state.total_fixed_liabilities)
+            / Decimal256::from_uint256(deposits)
+    };
+
+    let borrower_count = read_state(deps.storage)?.borrower_count;
+
+    Ok(MarketSummaryResponse {
+        state,
+        total_deposits,
+        rates,
+        stable_balance,
+        utilization,
+        borrower_count,
+    })
+}
+
+pub fn query_pending_config_changes(deps: Deps) -> StdResult<PendingConfigChangesResponse> {
+    let humanize = |addr: Option<CanonicalAddr>| -> StdResult<Option<String>> {
+        addr.map(|addr| Ok(deps.api.addr_humanize(&addr)?.to_string()))
+            .transpose()
+    };
+
+    let changes = read_pending_config_changes(deps.storage)?
+        .into_iter()
+        .map(|change| {
+            Ok(PendingConfigChangeResponse {
+                change: ConfigChange {
+                    owner_addr: humanize(change.owner_addr)?,
+                    interest_model: humanize(change.interest_model)?,
+                    max_borrow_factor: change.max_borrow_factor,
+                },
+                eta: change.eta,
+            })
+        })
+        .collect::<StdResult<Vec<PendingConfigChangeResponse>>>()?;
+
+    Ok(PendingConfigChangesResponse { changes })
+}
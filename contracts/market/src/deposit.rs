@@ -1,30 +1,196 @@
 use cosmwasm_bignumber::{Decimal256, Uint256};
 use cosmwasm_std::{
-    attr, to_binary, Addr, BankMsg, Coin, CosmosMsg, Deps, DepsMut, Env, MessageInfo, Response,
-    StdResult, Uint128, WasmMsg,
+    attr, to_binary, Addr, Binary, CosmosMsg, Deps, DepsMut, Env, Event, MessageInfo, Response,
+    StdError, StdResult, Uint128, WasmMsg,
 };
 
-use crate::borrow::{compute_interest, compute_reward};
+use crate::borrow::{compute_interest_with_snapshot, compute_reward, no_stable_available};
 use crate::error::ContractError;
-use crate::state::{read_config, read_state, store_state, Config, State};
+use crate::querier::query_overseer_config;
+use crate::snapshot::MarketSnapshot;
+use crate::stable::{query_stable_balance, stable_funds, stable_transfer_msg};
+use crate::state::{
+    push_locked_deposit, read_config, read_last_deposit, read_locked_deposit, read_referral_volume,
+    read_state, remove_locked_deposit, store_last_deposit, store_referral_volume, store_state,
+    Config, LockedDeposit, State,
+};
 
 use cw20::Cw20ExecuteMsg;
-use moneymarket::querier::{deduct_tax, query_balance, query_supply};
+use moneymarket::market::{
+    AterraToUnderlyingResponse, LockedDepositResponse, ReferralVolumeResponse,
+    RemainingDepositCapacityResponse, TotalDepositsResponse, TvlInReferenceResponse,
+    UnderlyingToAterraResponse,
+};
+use moneymarket::querier::query_price;
 
 pub fn deposit_stable(
     deps: DepsMut,
     env: Env,
     info: MessageInfo,
+    referrer: Option<Addr>,
+) -> Result<Response, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+    let deposit_amount = stable_funds(&config, &info)?;
+    deposit(
+        deps,
+        env,
+        info.sender,
+        deposit_amount,
+        referrer,
+        MintTo::Depositor,
+    )
+}
+
+pub fn deposit_protocol_owned(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+) -> Result<Response, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+
+    // permission check
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner_addr {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let deposit_amount = stable_funds(&config, &info)?;
+    deposit(
+        deps,
+        env,
+        info.sender,
+        deposit_amount,
+        None,
+        MintTo::ProtocolOwned,
+    )
+}
+
+/// The staking contract receives the aterra from the market, so `msg` has
+/// to name the staker; only allowed staking contracts are trusted to credit them
+pub fn deposit_and_stake(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    staking_contract: Addr,
+    msg: Binary,
 ) -> Result<Response, ContractError> {
     let config: Config = read_config(deps.storage)?;
+    if !config
+        .staking_contracts
+        .contains(&deps.api.addr_canonicalize(staking_contract.as_str())?)
+    {
+        return Err(ContractError::StakingContractNotAllowed(
+            staking_contract.to_string(),
+        ));
+    }
+
+    let deposit_amount = stable_funds(&config, &info)?;
+    deposit(
+        deps,
+        env,
+        info.sender,
+        deposit_amount,
+        None,
+        MintTo::Stake {
+            staking_contract,
+            msg,
+        },
+    )
+}
 
-    // Check base denom deposit
-    let deposit_amount: Uint256 = info
-        .funds
+pub fn deposit_stable_locked(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    term_blocks: u64,
+) -> Result<Response, ContractError> {
+    if term_blocks == 0 {
+        return Err(ContractError::InvalidLockTerm {});
+    }
+
+    let config: Config = read_config(deps.storage)?;
+    let deposit_amount = stable_funds(&config, &info)?;
+    let unlock_at = env.block.height + term_blocks;
+    deposit(
+        deps,
+        env,
+        info.sender,
+        deposit_amount,
+        None,
+        MintTo::Locked { unlock_at },
+    )
+}
+
+/// Max # of recipients a single deposit can be split across
+const MAX_SPLIT_RECIPIENTS: usize = 30;
+
+pub fn deposit_stable_split(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    recipients: Vec<(Addr, Uint256)>,
+) -> Result<Response, ContractError> {
+    if recipients.len() > MAX_SPLIT_RECIPIENTS {
+        return Err(ContractError::TooManySplitRecipients {
+            max: MAX_SPLIT_RECIPIENTS,
+        });
+    }
+
+    let config: Config = read_config(deps.storage)?;
+    let deposit_amount = stable_funds(&config, &info)?;
+    let split_amount = recipients
         .iter()
-        .find(|c| c.denom == config.stable_denom)
-        .map(|c| Uint256::from(c.amount))
-        .unwrap_or_else(Uint256::zero);
+        .fold(Uint256::zero(), |sum, (_, amount)| sum + *amount);
+    if split_amount != deposit_amount {
+        return Err(ContractError::SplitAmountMismatch {
+            deposit_amount: deposit_amount.into(),
+            split_amount: split_amount.into(),
+        });
+    }
+
+    if recipients.iter().any(|(_, amount)| amount.is_zero()) {
+        return Err(ContractError::ZeroDeposit(config.stable_denom));
+    }
+
+    deposit(
+        deps,
+        env,
+        info.sender,
+        deposit_amount,
+        None,
+        MintTo::Split { recipients },
+    )
+}
+
+/// Where the aterra minted by a deposit goes
+pub(crate) enum MintTo {
+    Depositor,
+    /// The market itself, alongside the initial seed
+    ProtocolOwned,
+    /// The market, which sends it on to `staking_contract` with `msg`
+    Stake {
+        staking_contract: Addr,
+        msg: Binary,
+    },
+    /// The market, on behalf of the depositor until `unlock_at`
+    Locked {
+        unlock_at: u64,
+    },
+    /// Each recipient, for its share of the deposit
+    Split {
+        recipients: Vec<(Addr, Uint256)>,
+    },
+}
+
+/// Deposit `deposit_amount` of stable already received by the contract
+pub(crate) fn deposit(
+    deps: DepsMut,
+    env: Env,
+    depositor: Addr,
+    deposit_amount: Uint256,
+    referrer: Option<Addr>,
+    mint_to: MintTo,
+) -> Result<Response, ContractError> {
+    let config: Config = read_config(deps.storage)?;
 
     // Cannot deposit zero amount
     if deposit_amount.is_zero() {
@@ -33,94 +199,575 @@ pub fn deposit_stable(
 
     // Update interest related state
     let mut state: State = read_state(deps.storage)?;
-    compute_interest(
+    let snapshot = MarketSnapshot::load(deps.as_ref(), &config, Some(deposit_amount))?;
+    let interest_accrued = compute_interest_with_snapshot(
         deps.as_ref(),
         &config,
         &mut state,
         env.block.height,
-        Some(deposit_amount),
+        &snapshot,
     )?;
     compute_reward(&mut state, env.block.height);
 
     // Load anchor token exchange rate with updated state
-    let exchange_rate =
-        compute_exchange_rate(deps.as_ref(), &config, &state, Some(deposit_amount))?;
-    let mint_amount = deposit_amount / exchange_rate;
+    let exchange_rate = snapshot.exchange_rate(&state);
+
+    if let Some(remaining) = remaining_deposit_capacity(&config, &snapshot, exchange_rate) {
+        if deposit_amount > remaining {
+            return Err(ContractError::DepositCapExceeded {
+                requested: deposit_amount.into(),
+                remaining: remaining.into(),
+            });
+        }
+    }
+
+    // Split deposits mint each share on its own, rounding each down
+    let mints: Vec<(Addr, Uint256)> = match &mint_to {
+        MintTo::Depositor => vec![(depositor.clone(), deposit_amount / exchange_rate)],
+        MintTo::Split { recipients } => recipients
+            .iter()
+            .map(|(recipient, amount)| (recipient.clone(), *amount / exchange_rate))
+            .collect(),
+        _ => vec![(env.contract.address.clone(), deposit_amount / exchange_rate)],
+    };
+    let mint_amount = mints
+        .iter()
+        .fold(Uint256::zero(), |sum, (_, amount)| sum + *amount);
 
     state.prev_aterra_supply += mint_amount;
+
+    // Locked deposits are bound by their own term instead of the redeem lockup.
+    // Only the depositor's lockup restarts; split recipients are left alone,
+    // or anyone could keep a victim from redeeming by naming them in a split
+    let mut lock_id = None;
+    match mint_to {
+        MintTo::ProtocolOwned => state.protocol_owned_aterra += mint_amount,
+        MintTo::Locked { unlock_at } => {
+            lock_id = Some(push_locked_deposit(
+                deps.storage,
+                &LockedDeposit {
+                    owner: deps.api.addr_canonicalize(depositor.as_str())?,
+                    aterra_amount: mint_amount,
+                    principal: deposit_amount,
+                    unlock_at,
+                },
+            )?);
+        }
+        _ => store_last_deposit(
+            deps.storage,
+            &deps.api.addr_canonicalize(depositor.as_str())?,
+            env.block.height,
+        )?,
+    }
+
     store_state(deps.storage, &state)?;
+
+    let aterra_contract = deps.api.addr_humanize(&config.aterra_contract)?.to_string();
+    let (action, stake_msg) = match mint_to {
+        MintTo::Depositor => ("deposit_stable", None),
+        MintTo::ProtocolOwned => ("deposit_protocol_owned", None),
+        MintTo::Locked { .. } => ("deposit_stable_locked", None),
+        MintTo::Split { .. } => ("deposit_stable_split", None),
+        MintTo::Stake {
+            staking_contract,
+            msg,
+        } => (
+            "deposit_and_stake",
+            Some(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: aterra_contract.clone(),
+                funds: vec![],
+                msg: to_binary(&Cw20ExecuteMsg::Send {
+                    contract: staking_contract.to_string(),
+                    amount: mint_amount.into(),
+                    msg,
+                })?,
+            })),
+        ),
+    };
+
+    let mut attributes = vec![
+        attr("action", action),
+        attr("depositor", depositor.as_str()),
+        attr("mint_amount", mint_amount),
+        attr("deposit_amount", deposit_amount),
+    ];
+    if let Some(lock_id) = lock_id {
+        attributes.push(attr("lock_id", lock_id.to_string()));
+    }
+
+    // Credit the referrer with the deposited volume
+    if let Some(referrer) = referrer {
+        let referrer_raw = deps.api.addr_canonicalize(referrer.as_str())?;
+        let volume = read_referral_volume(deps.storage, &referrer_raw) + deposit_amount;
+        store_referral_volume(deps.storage, &referrer_raw, &volume)?;
+        attributes.push(attr("referrer", referrer));
+    }
+
+    // Emitted as wasm-deposit, alike for every kind of deposit
+    let deposit_event = Event::new("deposit").add_attributes(vec![
+        attr("depositor", depositor.as_str()),
+        attr("deposit_amount", deposit_amount),
+        attr("mint_amount", mint_amount),
+        attr("exchange_rate", exchange_rate.to_string()),
+    ]);
+
+    let mint_msgs = mints
+        .into_iter()
+        .map(|(recipient, amount)| {
+            Ok(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: aterra_contract.clone(),
+                funds: vec![],
+                msg: to_binary(&Cw20ExecuteMsg::Mint {
+                    recipient: recipient.to_string(),
+                    amount: amount.into(),
+                })?,
+            }))
+        })
+        .collect::<StdResult<Vec<CosmosMsg>>>()?;
+
     Ok(Response::new()
-        .add_message(CosmosMsg::Wasm(WasmMsg::Execute {
-            contract_addr: deps.api.addr_humanize(&config.aterra_contract)?.to_string(),
-            funds: vec![],
-            msg: to_binary(&Cw20ExecuteMsg::Mint {
-                recipient: info.sender.to_string(),
-                amount: mint_amount.into(),
-            })?,
-        }))
-        .add_attributes(vec![
-            attr("action", "deposit_stable"),
-            attr("depositor", info.sender),
-            attr("mint_amount", mint_amount),
-            attr("deposit_amount", deposit_amount),
-        ]))
+        .add_events(interest_accrued)
+        .add_event(deposit_event)
+        .add_messages(mint_msgs)
+        .add_messages(stake_msg)
+        .add_attributes(attributes))
 }
 
+pub fn query_referral_volume(deps: Deps, referrer: Addr) -> StdResult<ReferralVolumeResponse> {
+    let volume = read_referral_volume(
+        deps.storage,
+        &deps.api.addr_canonicalize(referrer.as_str())?,
+    );
+
+    Ok(ReferralVolumeResponse {
+        referrer: referrer.to_string(),
+        volume,
+    })
+}
+
+pub fn query_remaining_deposit_capacity(
+    deps: Deps,
+    env: Env,
+) -> StdResult<RemainingDepositCapacityResponse> {
+    let config: Config = read_config(deps.storage)?;
+    let mut state: State = read_state(deps.storage)?;
+    let snapshot = MarketSnapshot::load(deps, &config, None)?;
+    compute_interest_with_snapshot(deps, &config, &mut state, env.block.height, &snapshot)?;
+
+    Ok(RemainingDepositCapacityResponse {
+        remaining: remaining_deposit_capacity(&config, &snapshot, snapshot.exchange_rate(&state)),
+    })
+}
+
+/// Stable left to deposit before the total deposits reach
+/// `max_total_deposits`, or None when uncapped
+fn remaining_deposit_capacity(
+    config: &Config,
+    snapshot: &MarketSnapshot,
+    exchange_rate: Decimal256,
+) -> Option<Uint256> {
+    config.max_total_deposits.map(|max_total_deposits| {
+        let total_deposits = snapshot.aterra_supply * exchange_rate;
+        if max_total_deposits > total_deposits {
+            max_total_deposits - total_deposits
+        } else {
+            Uint256::zero()
+        }
+    })
+}
+
+pub fn query_total_deposits(deps: Deps, env: Env) -> StdResult<TotalDepositsResponse> {
+    let config: Config = read_config(deps.storage)?;
+    let mut state: State = read_state(deps.storage)?;
+    let snapshot = MarketSnapshot::load(deps, &config, None)?;
+    compute_interest_with_snapshot(deps, &config, &mut state, env.block.height, &snapshot)?;
+
+    let exchange_rate = snapshot.exchange_rate(&state);
+
+    Ok(TotalDepositsResponse {
+        user_deposits: (snapshot.aterra_supply - state.protocol_owned_aterra) * exchange_rate,
+        protocol_deposits: state.protocol_owned_aterra * exchange_rate,
+    })
+}
+
+/// Prices older than the overseer's `price_timeframe` are rejected,
+/// as they are for borrow limits
+pub fn query_tvl_in_reference(
+    deps: Deps,
+    env: Env,
+    oracle: Option<Addr>,
+    reference_denom: String,
+) -> StdResult<TvlInReferenceResponse> {
+    let config: Config = read_config(deps.storage)?;
+    let overseer_config =
+        query_overseer_config(deps, deps.api.addr_humanize(&config.overseer_contract)?)?;
+    let oracle = match oracle {
+        Some(oracle) => oracle,
+        None => deps.api.addr_validate(&overseer_config.oracle_contract)?,
+    };
+
+    let price = query_price(
+        deps,
+        oracle,
+        config.stable_denom.to_string(),
+        reference_denom.to_string(),
+        None,
+    )
+    .map_err(|_| {
+        StdError::generic_err(format!(
+            "No {}/{} price available from the oracle",
+            config.stable_denom, reference_denom
+        ))
+    })?;
+
+    let valid_update_time = env
+        .block
+        .time
+        .seconds()
+        .saturating_sub(overseer_config.price_timeframe);
+    if price.last_updated_base < valid_update_time || price.last_updated_quote < valid_update_time {
+        return Err(StdError::generic_err(format!(
+            "{}/{} price is older than {} seconds",
+            config.stable_denom, reference_denom, overseer_config.price_timeframe
+        )));
+    }
+
+    let total_deposits = query_total_deposits(deps, env)?;
+    let total_underlying = total_deposits.user_deposits + total_deposits.protocol_deposits;
+
+    Ok(TvlInReferenceResponse {
+        total_underlying,
+        price: price.rate,
+        tvl: total_underlying * price.rate,
+    })
+}
+
+pub fn query_aterra_to_underlying(
+    deps: Deps,
+    env: Env,
+    aterra_amount: Uint256,
+    block_height: Option<u64>,
+) -> StdResult<AterraToUnderlyingResponse> {
+    let exchange_rate = query_exchange_rate(deps, env, block_height)?;
+    Ok(AterraToUnderlyingResponse {
+        underlying_amount: aterra_amount * exchange_rate,
+    })
+}
+
+pub fn query_underlying_to_aterra(
+    deps: Deps,
+    env: Env,
+    underlying_amount: Uint256,
+    block_height: Option<u64>,
+) -> StdResult<UnderlyingToAterraResponse> {
+    let exchange_rate = query_exchange_rate(deps, env, block_height)?;
+    Ok(UnderlyingToAterraResponse {
+        aterra_amount: underlying_amount / exchange_rate,
+    })
+}
+
+/// Exchange rate with interest accrued up to `block_height`
+fn query_exchange_rate(deps: Deps, env: Env, block_height: Option<u64>) -> StdResult<Decimal256> {
+    let config: Config = read_config(deps.storage)?;
+    let mut state: State = read_state(deps.storage)?;
+
+    let block_height = block_height.unwrap_or(env.block.height);
+    if block_height < state.last_interest_updated {
+        return Err(StdError::generic_err(
+            "block_height must bigger than last_interest_updated",
+        ));
+    }
+
+    let snapshot = MarketSnapshot::load(deps, &config, None)?;
+    compute_interest_with_snapshot(deps, &config, &mut state, block_height, &snapshot)?;
+    Ok(snapshot.exchange_rate(&state))
+}
+
+/// Redeems the sent `burn_amount` of aterra, or only the aterra
+/// `underlying_amount` takes at the exchange rate, returning the rest
 pub fn redeem_stable(
     deps: DepsMut,
     env: Env,
     sender: Addr,
     burn_amount: Uint128,
+    underlying_amount: Option<Uint256>,
 ) -> Result<Response, ContractError> {
     let config: Config = read_config(deps.storage)?;
+    if underlying_amount == Some(Uint256::zero()) {
+        return Err(ContractError::ZeroRedeem(config.stable_denom));
+    }
 
-    // Update interest related state
+    assert_redeem_lockup(deps.as_ref(), &config, &env, &sender)?;
+
+    // Update interest related state; interest accrued in this block already
+    // stored the exchange rate and synced the supply, so the fast path only
+    // needs the balance to check liquidity against
     let mut state: State = read_state(deps.storage)?;
-    compute_interest(deps.as_ref(), &config, &mut state, env.block.height, None)?;
+    let (snapshot, interest_accrued, exchange_rate) =
+        if config.redeem_fast_path && state.last_interest_updated == env.block.height {
+            let snapshot = MarketSnapshot {
+                balance: query_stable_balance(deps.as_ref(), &config)?,
+                aterra_supply: state.prev_aterra_supply,
+            };
+            (snapshot, None, state.prev_exchange_rate)
+        } else {
+            let snapshot = MarketSnapshot::load(deps.as_ref(), &config, None)?;
+            let interest_accrued = compute_interest_with_snapshot(
+                deps.as_ref(),
+                &config,
+                &mut state,
+                env.block.height,
+                &snapshot,
+            )?;
+
+            // Load anchor token exchange rate with updated state
+            let exchange_rate = snapshot.exchange_rate(&state);
+            (snapshot, interest_accrued, exchange_rate)
+        };
     compute_reward(&mut state, env.block.height);
+    let mut burn_amount = Uint256::from(burn_amount);
+    let mut redeem_amount = burn_amount * exchange_rate;
 
-    // Load anchor token exchange rate with updated state
-    let exchange_rate = compute_exchange_rate(deps.as_ref(), &config, &state, None)?;
-    let redeem_amount = Uint256::from(burn_amount) * exchange_rate;
+    // Dust rounds down to nothing; unless configured to burn it, fail
+    // rather than leave the redeemer with a send of zero
+    if redeem_amount.is_zero() && underlying_amount.is_none() && !config.burn_redeem_dust {
+        return Err(ContractError::RedeemDust {
+            burn_amount: burn_amount.into(),
+        });
+    }
+
+    let mut refund_amount = Uint256::zero();
+    if let Some(underlying_amount) = underlying_amount {
+        // Round the burn up, so the payout is never short
+        let mut required = underlying_amount / exchange_rate;
+        if required * exchange_rate < underlying_amount {
+            required += Uint256::one();
+        }
+
+        if required > burn_amount {
+            return Err(ContractError::InsufficientAterraSent {
+                required: required.into(),
+                sent: burn_amount.into(),
+            });
+        }
+
+        refund_amount = burn_amount - required;
+        burn_amount = required;
+        redeem_amount = underlying_amount;
+    }
+
+    // Assert redeem amount; under a haircut, fill what liquidity allows
+    let mut haircut_amount = Uint256::zero();
+    if let Err(err) = assert_redeem_amount(&config, &state, snapshot.balance, redeem_amount) {
+        let current_balance = Decimal256::from_uint256(snapshot.balance);
+        let haircut = match config.low_liquidity_haircut {
+            Some(haircut) if current_balance > state.total_reserves => haircut,
+            _ => return Err(err),
+        };
+
+        let available = current_balance - state.total_reserves;
+        let fill_burn_amount = available * Uint256::one() / exchange_rate;
+        if fill_burn_amount.is_zero() {
+            return Err(err);
+        }
+
+        refund_amount += burn_amount - fill_burn_amount;
+        burn_amount = fill_burn_amount;
+
+        let fill_amount = burn_amount * exchange_rate;
+        haircut_amount = fill_amount * haircut;
+        redeem_amount = fill_amount - haircut_amount;
+        state.total_reserves += Decimal256::from_uint256(haircut_amount);
+    }
+
+    state.prev_aterra_supply = state.prev_aterra_supply - burn_amount;
+    store_state(deps.storage, &state)?;
+
+    // The supply before the burn, from the token or, on the fast path,
+    // as tracked since this block's accrual
+    let aterra_supply = snapshot.aterra_supply;
+
+    // Emitted as wasm-redeem
+    let redeem_event = Event::new("redeem").add_attributes(vec![
+        attr("redeemer", sender.as_str()),
+        attr("burn_amount", burn_amount),
+        attr("redeem_amount", redeem_amount),
+        attr("exchange_rate", exchange_rate.to_string()),
+    ]);
+
+    let aterra_contract = deps.api.addr_humanize(&config.aterra_contract)?.to_string();
+    let mut messages = vec![CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: aterra_contract.clone(),
+        funds: vec![],
+        msg: to_binary(&Cw20ExecuteMsg::Burn {
+            amount: burn_amount.into(),
+        })?,
+    })];
+
+    let mut attributes = vec![
+        attr("action", "redeem_stable"),
+        attr("burn_amount", burn_amount),
+        attr("redeem_amount", redeem_amount),
+        attr("aterra_supply_after", aterra_supply - burn_amount),
+    ];
+
+    if !haircut_amount.is_zero() {
+        attributes.push(attr("haircut_amount", haircut_amount));
+    }
 
-    let current_balance = query_balance(
+    // Return the aterra left unburnt
+    if !refund_amount.is_zero() {
+        messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: aterra_contract,
+            funds: vec![],
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: sender.to_string(),
+                amount: refund_amount.into(),
+            })?,
+        }));
+        attributes.push(attr("refund_amount", refund_amount));
+    }
+
+    if !redeem_amount.is_zero() {
+        messages.push(stable_transfer_msg(
+            deps.as_ref(),
+            &config,
+            sender.to_string(),
+            redeem_amount,
+        )?);
+    }
+
+    Ok(Response::new()
+        .add_events(interest_accrued)
+        .add_event(redeem_event)
+        .add_messages(messages)
+        .add_attributes(attributes))
+}
+
+pub fn redeem_locked(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    lock_id: u64,
+) -> Result<Response, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+    let locked_deposit = read_locked_deposit(deps.storage, lock_id)?;
+    if deps.api.addr_canonicalize(info.sender.as_str())? != locked_deposit.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    if env.block.height < locked_deposit.unlock_at {
+        return Err(ContractError::RedeemLocked {
+            unlock_at: locked_deposit.unlock_at,
+        });
+    }
+
+    // Update interest related state
+    let mut state: State = read_state(deps.storage)?;
+    let snapshot = MarketSnapshot::load(deps.as_ref(), &config, None)?;
+    let interest_accrued = compute_interest_with_snapshot(
         deps.as_ref(),
-        env.contract.address,
-        config.stable_denom.to_string(),
+        &config,
+        &mut state,
+        env.block.height,
+        &snapshot,
     )?;
+    compute_reward(&mut state, env.block.height);
+
+    let exchange_rate = snapshot.exchange_rate(&state);
+    let burn_amount = locked_deposit.aterra_amount;
+    let redeem_amount = burn_amount * exchange_rate;
+
+    // The boost is a share of the yield earned over the term, funded by
+    // the reserves (see locked_deposit_boost) as far as they cover it
+    let boost_amount = if redeem_amount > locked_deposit.principal {
+        std::cmp::min(
+            (redeem_amount - locked_deposit.principal) * config.locked_deposit_boost,
+            state.total_reserves * Uint256::one(),
+        )
+    } else {
+        Uint256::zero()
+    };
+    state.total_reserves = state.total_reserves - Decimal256::from_uint256(boost_amount);
 
     // Assert redeem amount
-    assert_redeem_amount(&config, &state, current_balance, redeem_amount)?;
+    assert_redeem_amount(
+        &config,
+        &state,
+        snapshot.balance,
+        redeem_amount + boost_amount,
+    )?;
 
-    state.prev_aterra_supply = state.prev_aterra_supply - Uint256::from(burn_amount);
+    state.prev_aterra_supply = state.prev_aterra_supply - burn_amount;
     store_state(deps.storage, &state)?;
+    remove_locked_deposit(deps.storage, lock_id);
+
+    // Emitted as wasm-redeem, alike for every kind of redemption
+    let redeem_event = Event::new("redeem").add_attributes(vec![
+        attr("redeemer", info.sender.as_str()),
+        attr("burn_amount", burn_amount),
+        attr("redeem_amount", redeem_amount),
+        attr("exchange_rate", exchange_rate.to_string()),
+    ]);
+
     Ok(Response::new()
+        .add_events(interest_accrued)
+        .add_event(redeem_event)
         .add_messages(vec![
             CosmosMsg::Wasm(WasmMsg::Execute {
                 contract_addr: deps.api.addr_humanize(&config.aterra_contract)?.to_string(),
                 funds: vec![],
                 msg: to_binary(&Cw20ExecuteMsg::Burn {
-                    amount: burn_amount,
+                    amount: burn_amount.into(),
                 })?,
             }),
-            CosmosMsg::Bank(BankMsg::Send {
-                to_address: sender.to_string(),
-                amount: vec![deduct_tax(
-                    deps.as_ref(),
-                    Coin {
-                        denom: config.stable_denom,
-                        amount: redeem_amount.into(),
-                    },
-                )?],
-            }),
+            stable_transfer_msg(
+                deps.as_ref(),
+                &config,
+                info.sender.to_string(),
+                redeem_amount + boost_amount,
+            )?,
         ])
         .add_attributes(vec![
-            attr("action", "redeem_stable"),
+            attr("action", "redeem_locked"),
+            attr("lock_id", lock_id.to_string()),
             attr("burn_amount", burn_amount),
             attr("redeem_amount", redeem_amount),
+            attr("boost_amount", boost_amount),
         ]))
 }
 
+pub fn query_locked_deposit(deps: Deps, lock_id: u64) -> StdResult<LockedDepositResponse> {
+    let locked_deposit = read_locked_deposit(deps.storage, lock_id)?;
+    Ok(LockedDepositResponse {
+        lock_id,
+        owner: deps.api.addr_humanize(&locked_deposit.owner)?.to_string(),
+        aterra_amount: locked_deposit.aterra_amount,
+        principal: locked_deposit.principal,
+        unlock_at: locked_deposit.unlock_at,
+    })
+}
+
+/// aterra is transferable, so the lockup only binds the
+/// address that deposited, from its latest deposit
+pub(crate) fn assert_redeem_lockup(
+    deps: Deps,
+    config: &Config,
+    env: &Env,
+    sender: &Addr,
+) -> Result<(), ContractError> {
+    if config.redeem_lockup > 0 {
+        let last_deposit =
+            read_last_deposit(deps.storage, &deps.api.addr_canonicalize(sender.as_str())?);
+        let unlock_at = last_deposit + config.redeem_lockup;
+        if last_deposit > 0 && env.block.height < unlock_at {
+            return Err(ContractError::RedeemLocked { unlock_at });
+        }
+    }
+
+    Ok(())
+}
+
 fn assert_redeem_amount(
     config: &Config,
     state: &State,
@@ -130,30 +777,17 @@ fn assert_redeem_amount(
     let current_balance = Decimal256::from_uint256(current_balance);
     let redeem_amount = Decimal256::from_uint256(redeem_amount);
     if redeem_amount + state.total_reserves > current_balance {
-        return Err(ContractError::NoStableAvailable(
-            config.stable_denom.clone(),
+        return Err(no_stable_available(
+            config,
+            state,
+            current_balance,
+            redeem_amount,
         ));
     }
 
     Ok(())
 }
 
-pub(crate) fn compute_exchange_rate(
-    deps: Deps,
-    config: &Config,
-    state: &State,
-    deposit_amount: Option<Uint256>,
-) -> StdResult<Decimal256> {
-    let aterra_supply = query_supply(deps, deps.api.addr_humanize(&config.aterra_contract)?)?;
-    let balance = query_balance(
-        deps,
-        deps.api.addr_humanize(&config.contract_addr)?,
-        config.stable_denom.to_string(),
-    )? - deposit_amount.unwrap_or_else(Uint256::zero);
-
-    Ok(compute_exchange_rate_raw(state, aterra_supply, balance))
-}
-
 pub fn compute_exchange_rate_raw(
     state: &State,
     aterra_supply: Uint256,
@@ -164,7 +798,10 @@ pub fn compute_exchange_rate_raw(
     }
 
     // (aterra / stable_denom)
-    // exchange_rate = (balance + total_liabilities - total_reserves) / aterra_supply
-    (Decimal256::from_uint256(contract_balance) + state.total_liabilities - state.total_reserves)
+    // exchange_rate = (balance + total_liabilities + total_fixed_liabilities - total_reserves) / aterra_supply
+    (Decimal256::from_uint256(contract_balance)
+        + state.total_liabilities
+        + state.total_fixed_liabilities
+        - state.total_reserves)
         / Decimal256::from_uint256(aterra_supply)
 }
@@ -3,36 +3,259 @@ use thiserror::Error;
 
 #[derive(Error, Debug, PartialEq)]
 pub enum ContractError {
-    #[error("{0}")]
+    #[error("[E1] {0}")]
     Std(#[from] StdError),
 
-    #[error("{0}")]
+    #[error("[E2] {0}")]
     OverflowError(#[from] OverflowError),
 
-    #[error("Unauthorized")]
+    #[error("[E3] Unauthorized")]
     Unauthorized {},
 
-    #[error("Borrow amount too high; Loan liability becomes greater than borrow limit: {0}")]
-    BorrowExceedsLimit(u128),
+    #[error("[E4] Cannot sweep {0}; it is held on behalf of depositors")]
+    CannotSweep(String),
 
-    #[error("Must deposit initial funds {0}{0}")]
-    InitialFundsNotDeposited(u128, String),
+    #[error("[E5] Borrow is in cooldown until block {ready_at}")]
+    BorrowCooldown { ready_at: u64 },
 
-    #[error("Invalid reply ID")]
+    #[error("[E6] Guardian has already voted on this proposal")]
+    AlreadyVoted {},
+
+    #[error("[E7] aterra instantiation failed: {0}")]
+    AterraInstantiationFailed(String),
+
+    #[error("[E8] Borrow amount too high; Loan liability {liability} becomes greater than borrow limit: {borrow_limit}")]
+    BorrowExceedsLimit { liability: u128, borrow_limit: u128 },
+
+    #[error(
+        "[E9] Borrow amount {requested} exceeds the {remaining} left under the per block limit"
+    )]
+    BorrowPerBlockExceeded { requested: u128, remaining: u128 },
+
+    #[error(
+        "[E10] Borrow amount {requested} exceeds the per transaction limit {max_borrow_per_tx}"
+    )]
+    BorrowPerTxExceeded {
+        requested: u128,
+        max_borrow_per_tx: u128,
+    },
+
+    #[error("[E11] Reward claims are paused")]
+    ClaimsPaused {},
+
+    #[error("[E12] Config change is timelocked until block {eta}")]
+    ConfigChangeTimelocked { eta: u64 },
+
+    #[error("[E13] {0} contract is already registered")]
+    ContractAlreadyRegistered(String),
+
+    #[error("[E14] Deposit amount {requested} exceeds the {remaining} left under the deposit cap")]
+    DepositCapExceeded { requested: u128, remaining: u128 },
+
+    #[error("[E51] Borrow amount {requested} exceeds the {remaining} left to the delegate")]
+    DelegatedBorrowExceeded { requested: u128, remaining: u128 },
+
+    #[error("[E15] Cannot send {denom} along; only the stable is accepted")]
+    ExtraFundsSent { denom: String },
+
+    #[error("[E16] Cannot change the rate mode of an open position")]
+    FixedRateMismatch {},
+
+    #[error("[E17] Must deposit initial funds {expected}{denom}; received {actual}{denom}")]
+    InitialFundsNotDeposited {
+        expected: u128,
+        actual: u128,
+        denom: String,
+    },
+
+    #[error(
+        "[E18] Borrow hook must execute the borrow recipient without funds, outside the market contracts"
+    )]
+    InvalidBorrowHook {},
+
+    #[error("[E19] Sent {sent} aterra; the requested underlying takes {required}")]
+    InsufficientAterraSent { required: u128, sent: u128 },
+
+    #[error("[E54] Not enough {denom} liquidity to fill the borrow of {requested}")]
+    InsufficientLiquidity { denom: String, requested: u128 },
+
+    #[error("[E20] Invalid aterra name {0}; must be 3 to 50 characters")]
+    InvalidAterraName(String),
+
+    #[error("[E21] Invalid aterra symbol {0}; must be 3 to 12 letters or dashes")]
+    InvalidAterraSymbol(String),
+
+    #[error("[E22] Blocks per year must be greater than 0")]
+    InvalidBlocksPerYear {},
+
+    #[error("[E23] Config change eta must be at least block {min_eta}")]
+    InvalidConfigChangeEta { min_eta: u64 },
+
+    #[error("[E24] Guardian threshold must be between 1 and the # of guardians")]
+    InvalidGuardianThreshold {},
+
+    #[error(
+        "[E25] last_interest_updated can only be moved backward, up to the current block height"
+    )]
+    InvalidLastInterestUpdated {},
+
+    #[error("[E52] Liquidation fee rate must not exceed one")]
+    InvalidLiquidationFeeRate {},
+
+    #[error("[E26] Low liquidity haircut must be less than one")]
+    InvalidLowLiquidityHaircut {},
+
+    #[error("[E27] Lock term must be at least one block")]
+    InvalidLockTerm {},
+
+    #[error("[E28] Max borrow factor must not exceed one")]
+    InvalidMaxBorrowFactor {},
+
+    #[error("[E29] Invalid param value {0}")]
+    InvalidParamValue(String),
+
+    #[error("[E30] Invalid recipient {0}")]
+    InvalidRecipient(String),
+
+    #[error("[E31] Invalid reply data; expected a MsgInstantiateContractResponse")]
+    InvalidReplyData {},
+
+    #[error("[E32] Invalid reply ID")]
     InvalidReplyId {},
 
-    #[error("Exceeds {0} max borrow factor; borrow demand too high")]
-    MaxBorrowFactorReached(String),
+    #[error("[E33] Reward tenure tiers must ascend in tenure with multipliers of at least one, never decreasing")]
+    InvalidRewardTenureTiers {},
 
-    #[error("Invalid request: \"redeem stable\" message not included in request")]
+    #[error("[E34] Invalid stable denom {0}; must be a micro denom such as uusd")]
+    InvalidStableDenom(String),
+
+    #[error("[E35] Borrow leaves {available} available, below the liquidity floor {floor}")]
+    LiquidityRatioFloor { available: u128, floor: u128 },
+
+    #[error(
+        "[E36] Exceeds {denom} max borrow factor; requested {requested}, borrowable {borrowable}"
+    )]
+    MaxBorrowFactorReached {
+        denom: String,
+        requested: u128,
+        borrowable: u128,
+    },
+
+    #[error("[E37] Invalid request: \"redeem stable\" message not included in request")]
     MissingRedeemStableHook {},
 
-    #[error("Not enough {0} available; borrow demand too high")]
-    NoStableAvailable(String),
+    #[error("[E38] No config change queued")]
+    NoPendingConfigChange {},
+
+    #[error("[E39] Not enough {denom} available; requested {requested}, available {available}")]
+    NoStableAvailable {
+        denom: String,
+        requested: u128,
+        available: u128,
+    },
+
+    #[error("[E40] Proposal is no longer open")]
+    ProposalClosed {},
 
-    #[error("Deposit amount must be greater than 0 {0}")]
+    #[error("[E41] Proposal has {approvals} approvals; {threshold} are needed")]
+    ProposalNotPassed { approvals: u32, threshold: u32 },
+
+    #[error(
+        "[E42] {burn_amount} aterra is worth less than one unit of stable and cannot be redeemed"
+    )]
+    RedeemDust { burn_amount: u128 },
+
+    #[error("[E43] Redeem is locked until block {unlock_at}")]
+    RedeemLocked { unlock_at: u64 },
+
+    #[error("[E44] Split shares add up to {split_amount}, not the {deposit_amount} deposited")]
+    SplitAmountMismatch {
+        deposit_amount: u128,
+        split_amount: u128,
+    },
+
+    #[error("[E53] {0} is not an allowed staking contract")]
+    StakingContractNotAllowed(String),
+
+    #[error("[E45] aterra supply {actual} exceeds the tracked supply of {expected}")]
+    SupplyMismatch { expected: u128, actual: u128 },
+
+    #[error("[E46] Cannot split a deposit across more than {max} recipients")]
+    TooManySplitRecipients { max: usize },
+
+    #[error("[E47] Borrow amount must be greater than 0 {0}")]
+    ZeroBorrow(String),
+
+    #[error("[E48] Deposit amount must be greater than 0 {0}")]
     ZeroDeposit(String),
 
-    #[error("Repay amount must be greater than 0 {0}")]
+    #[error("[E49] Redeem amount must be greater than 0 {0}")]
+    ZeroRedeem(String),
+
+    #[error("[E50] Repay amount must be greater than 0 {0}")]
     ZeroRepay(String),
 }
+
+impl ContractError {
+    /// Stable numeric code for clients to map errors by, as the messages
+    /// may change; a new variant takes the next unused code. Every message
+    /// starts with it as `[E<code>]`, so it survives into the tx log
+    pub fn code(&self) -> u32 {
+        match self {
+            ContractError::Std(_) => 1,
+            ContractError::OverflowError(_) => 2,
+            ContractError::Unauthorized {} => 3,
+            ContractError::CannotSweep(_) => 4,
+            ContractError::BorrowCooldown { .. } => 5,
+            ContractError::AlreadyVoted {} => 6,
+            ContractError::AterraInstantiationFailed(_) => 7,
+            ContractError::BorrowExceedsLimit { .. } => 8,
+            ContractError::BorrowPerBlockExceeded { .. } => 9,
+            ContractError::BorrowPerTxExceeded { .. } => 10,
+            ContractError::ClaimsPaused {} => 11,
+            ContractError::ConfigChangeTimelocked { .. } => 12,
+            ContractError::ContractAlreadyRegistered(_) => 13,
+            ContractError::DepositCapExceeded { .. } => 14,
+            ContractError::ExtraFundsSent { .. } => 15,
+            ContractError::FixedRateMismatch {} => 16,
+            ContractError::InitialFundsNotDeposited { .. } => 17,
+            ContractError::InvalidBorrowHook {} => 18,
+            ContractError::InsufficientAterraSent { .. } => 19,
+            ContractError::InvalidAterraName(_) => 20,
+            ContractError::InvalidAterraSymbol(_) => 21,
+            ContractError::InvalidBlocksPerYear {} => 22,
+            ContractError::InvalidConfigChangeEta { .. } => 23,
+            ContractError::InvalidGuardianThreshold {} => 24,
+            ContractError::InvalidLastInterestUpdated {} => 25,
+            ContractError::InvalidLowLiquidityHaircut {} => 26,
+            ContractError::InvalidLockTerm {} => 27,
+            ContractError::InvalidMaxBorrowFactor {} => 28,
+            ContractError::InvalidParamValue(_) => 29,
+            ContractError::InvalidRecipient(_) => 30,
+            ContractError::InvalidReplyData {} => 31,
+            ContractError::InvalidReplyId {} => 32,
+            ContractError::InvalidRewardTenureTiers {} => 33,
+            ContractError::InvalidStableDenom(_) => 34,
+            ContractError::LiquidityRatioFloor { .. } => 35,
+            ContractError::MaxBorrowFactorReached { .. } => 36,
+            ContractError::MissingRedeemStableHook {} => 37,
+            ContractError::NoPendingConfigChange {} => 38,
+            ContractError::NoStableAvailable { .. } => 39,
+            ContractError::ProposalClosed {} => 40,
+            ContractError::ProposalNotPassed { .. } => 41,
+            ContractError::RedeemDust { .. } => 42,
+            ContractError::RedeemLocked { .. } => 43,
+            ContractError::SplitAmountMismatch { .. } => 44,
+            ContractError::SupplyMismatch { .. } => 45,
+            ContractError::TooManySplitRecipients { .. } => 46,
+            ContractError::ZeroBorrow(_) => 47,
+            ContractError::ZeroDeposit(_) => 48,
+            ContractError::ZeroRedeem(_) => 49,
+            ContractError::ZeroRepay(_) => 50,
+            ContractError::DelegatedBorrowExceeded { .. } => 51,
+            ContractError::InvalidLiquidationFeeRate {} => 52,
+            ContractError::StakingContractNotAllowed(_) => 53,
+            ContractError::InsufficientLiquidity { .. } => 54,
+        }
+    }
+}
@@ -2,8 +2,12 @@ pub mod borrow;
 pub mod contract;
 pub mod deposit;
 pub mod error;
+pub mod proposal;
 pub mod querier;
+pub mod rates;
 pub mod response;
+pub mod snapshot;
+pub mod stable;
 pub mod state;
 
 #[cfg(test)]
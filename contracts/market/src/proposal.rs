@@ -0,0 +1,168 @@
+use std::str::FromStr;
+
+use cosmwasm_std::{attr, CanonicalAddr, Deps, DepsMut, MessageInfo, Response, StdResult};
+
+use moneymarket::market::{ConfigParam, ProposalResponse, ProposalStatus};
+
+use crate::contract::validate_liquidation_fee_rate;
+use crate::error::ContractError;
+use crate::state::{
+    push_proposal, read_config, read_proposal, read_proposal_vote, store_config, store_proposal,
+    store_proposal_vote, Config, Proposal,
+};
+
+pub fn propose_param(
+    deps: DepsMut,
+    info: MessageInfo,
+    param: ConfigParam,
+    value: String,
+) -> Result<Response, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+    let proposer = assert_guardian(deps.as_ref(), &config, &info)?;
+
+    // Reject unparsable values now rather than at execution
+    apply_param(&mut config.clone(), &param, &value)?;
+
+    let proposal_id = push_proposal(
+        deps.storage,
+        &Proposal {
+            param: param.clone(),
+            value: value.clone(),
+            proposer,
+            approvals: 0,
+            rejections: 0,
+            status: ProposalStatus::Open,
+        },
+    )?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "propose_param"),
+        attr("proposal_id", proposal_id.to_string()),
+        attr("param", param.to_string()),
+        attr("value", value),
+    ]))
+}
+
+pub fn vote_param(
+    deps: DepsMut,
+    info: MessageInfo,
+    proposal_id: u64,
+    approve: bool,
+) -> Result<Response, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+    let voter = assert_guardian(deps.as_ref(), &config, &info)?;
+
+    let mut proposal = read_proposal(deps.storage, proposal_id)?;
+    if proposal.status != ProposalStatus::Open {
+        return Err(ContractError::ProposalClosed {});
+    }
+
+    if read_proposal_vote(deps.storage, proposal_id, &voter)?.is_some() {
+        return Err(ContractError::AlreadyVoted {});
+    }
+
+    store_proposal_vote(deps.storage, proposal_id, &voter, approve)?;
+    if approve {
+        proposal.approvals += 1;
+    } else {
+        proposal.rejections += 1;
+    }
+
+    // Once the remaining guardians cannot reach the threshold,
+    // the proposal can never pass
+    let max_approvals = (config.guardians.len() as u32).saturating_sub(proposal.rejections);
+    if max_approvals < config.guardian_threshold {
+        proposal.status = ProposalStatus::Rejected;
+    }
+
+    store_proposal(deps.storage, proposal_id, &proposal)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "vote_param"),
+        attr("proposal_id", proposal_id.to_string()),
+        attr("approve", approve.to_string()),
+        attr("approvals", proposal.approvals.to_string()),
+        attr("rejections", proposal.rejections.to_string()),
+    ]))
+}
+
+pub fn execute_param(deps: DepsMut, proposal_id: u64) -> Result<Response, ContractError> {
+    let mut config: Config = read_config(deps.storage)?;
+    let mut proposal = read_proposal(deps.storage, proposal_id)?;
+    if proposal.status != ProposalStatus::Open {
+        return Err(ContractError::ProposalClosed {});
+    }
+
+    // An empty guardian set disables the flow, even for passed proposals
+    if config.guardians.is_empty() || proposal.approvals < config.guardian_threshold {
+        return Err(ContractError::ProposalNotPassed {
+            approvals: proposal.approvals,
+            threshold: config.guardian_threshold,
+        });
+    }
+
+    apply_param(&mut config, &proposal.param, &proposal.value)?;
+    store_config(deps.storage, &config)?;
+
+    proposal.status = ProposalStatus::Executed;
+    store_proposal(deps.storage, proposal_id, &proposal)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "execute_param"),
+        attr("proposal_id", proposal_id.to_string()),
+        attr("param", proposal.param.to_string()),
+        attr("value", proposal.value),
+    ]))
+}
+
+pub fn query_proposal(deps: Deps, id: u64) -> StdResult<ProposalResponse> {
+    let proposal = read_proposal(deps.storage, id)?;
+    Ok(ProposalResponse {
+        id,
+        param: proposal.param,
+        value: proposal.value,
+        proposer: deps.api.addr_humanize(&proposal.proposer)?.to_string(),
+        approvals: proposal.approvals,
+        rejections: proposal.rejections,
+        status: proposal.status,
+    })
+}
+
+fn assert_guardian(
+    deps: Deps,
+    config: &Config,
+    info: &MessageInfo,
+) -> Result<CanonicalAddr, ContractError> {
+    let sender = deps.api.addr_canonicalize(info.sender.as_str())?;
+    if !config.guardians.contains(&sender) {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    Ok(sender)
+}
+
+fn apply_param(config: &mut Config, param: &ConfigParam, value: &str) -> Result<(), ContractError> {
+    match param {
+        ConfigParam::BorrowGracePeriod => config.borrow_grace_period = parse_param(param, value)?,
+        ConfigParam::BorrowCooldown => config.borrow_cooldown = parse_param(param, value)?,
+        ConfigParam::RedeemLockup => config.redeem_lockup = parse_param(param, value)?,
+        ConfigParam::MinLiquidityRatio => config.min_liquidity_ratio = parse_param(param, value)?,
+        ConfigParam::LiquidationFeeRate => {
+            let liquidation_fee_rate = parse_param(param, value)?;
+            validate_liquidation_fee_rate(liquidation_fee_rate)?;
+            config.liquidation_fee_rate = liquidation_fee_rate;
+        }
+        ConfigParam::UtilizationSensitivity => {
+            config.utilization_sensitivity = parse_param(param, value)?
+        }
+        ConfigParam::ReserveTarget => config.reserve_target = Some(parse_param(param, value)?),
+        ConfigParam::MaxReserves => config.max_reserves = Some(parse_param(param, value)?),
+        ConfigParam::MaxBorrowPerTx => config.max_borrow_per_tx = Some(parse_param(param, value)?),
+    }
+
+    Ok(())
+}
+
+fn parse_param<T: FromStr>(param: &ConfigParam, value: &str) -> Result<T, ContractError> {
+    T::from_str(value).map_err(|_| ContractError::InvalidParamValue(format!("{}={}", param, value)))
+}
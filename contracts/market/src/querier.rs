@@ -1,10 +1,17 @@
 use cosmwasm_bignumber::{Decimal256, Uint256};
 use cosmwasm_std::{to_binary, Addr, Deps, QueryRequest, StdResult, WasmQuery};
+use cw20::{Cw20QueryMsg, TokenInfoResponse};
+
+use anchor_token::distributor::{
+    ConfigResponse as DistributorConfigResponse, QueryMsg as DistributorQueryMsg,
+};
 
 use moneymarket::distribution_model::{AncEmissionRateResponse, QueryMsg as DistributionQueryMsg};
 use moneymarket::interest_model::{BorrowRateResponse, QueryMsg as InterestQueryMsg};
 use moneymarket::overseer::{BorrowLimitResponse, ConfigResponse, QueryMsg as OverseerQueryMsg};
 
+use crate::state::{Config, State};
+
 pub fn query_borrow_rate(
     deps: Deps,
     interest_addr: Addr,
@@ -25,6 +32,78 @@ pub fn query_borrow_rate(
     Ok(borrow_rate)
 }
 
+/// Borrow rate of the market's interest model; when it errors, the rate of
+/// `fallback_interest_model`, then `fallback_borrow_rate`.
+/// Also returns whether a fallback was used
+pub fn query_market_borrow_rate(
+    deps: Deps,
+    config: &Config,
+    market_balance: Uint256,
+    total_liabilities: Decimal256,
+    total_reserves: Decimal256,
+) -> StdResult<(Decimal256, bool)> {
+    let err = match query_borrow_rate(
+        deps,
+        deps.api.addr_humanize(&config.interest_model)?,
+        market_balance,
+        total_liabilities,
+        total_reserves,
+    ) {
+        Ok(borrow_rate) => return Ok((borrow_rate.rate, false)),
+        Err(err) => err,
+    };
+
+    if let Some(fallback_interest_model) = &config.fallback_interest_model {
+        if let Ok(borrow_rate) = query_borrow_rate(
+            deps,
+            deps.api.addr_humanize(fallback_interest_model)?,
+            market_balance,
+            total_liabilities,
+            total_reserves,
+        ) {
+            return Ok((borrow_rate.rate, true));
+        }
+    }
+
+    match config.fallback_borrow_rate {
+        Some(fallback_borrow_rate) => Ok((fallback_borrow_rate, true)),
+        None => Err(err),
+    }
+}
+
+/// query_market_borrow_rate, reusing the rate cached in `state` until
+/// `rate_refresh_interval` blocks have passed; fallback rates are not cached
+pub fn cached_market_borrow_rate(
+    deps: Deps,
+    config: &Config,
+    state: &mut State,
+    block_height: u64,
+    market_balance: Uint256,
+) -> StdResult<(Decimal256, bool)> {
+    if let Some(cached_borrow_rate) = state.cached_borrow_rate {
+        if block_height < state.borrow_rate_cached_at + config.rate_refresh_interval {
+            return Ok((cached_borrow_rate, false));
+        }
+    }
+
+    let (borrow_rate, used_fallback) = query_market_borrow_rate(
+        deps,
+        config,
+        market_balance,
+        state.total_liabilities + state.total_fixed_liabilities,
+        state.total_reserves,
+    )?;
+
+    if config.rate_refresh_interval > 0 && !used_fallback {
+        state.cached_borrow_rate = Some(borrow_rate);
+        state.borrow_rate_cached_at = block_height;
+    } else {
+        state.cached_borrow_rate = None;
+    }
+
+    Ok((borrow_rate, used_fallback))
+}
+
 pub fn query_borrow_limit(
     deps: Deps,
     overseer_addr: Addr,
@@ -65,12 +144,34 @@ pub fn query_anc_emission_rate(
     Ok(anc_emission_rate)
 }
 
+pub fn query_overseer_config(deps: Deps, overseer_contract: Addr) -> StdResult<ConfigResponse> {
+    deps.querier.query(&QueryRequest::Wasm(WasmQuery::Smart {
+        contract_addr: overseer_contract.to_string(),
+        msg: to_binary(&OverseerQueryMsg::Config {})?,
+    }))
+}
+
 pub fn query_target_deposit_rate(deps: Deps, overseer_contract: Addr) -> StdResult<Decimal256> {
-    let overseer_config: ConfigResponse =
+    let overseer_config = query_overseer_config(deps, overseer_contract)?;
+    Ok(overseer_config.target_deposit_rate)
+}
+
+pub fn query_distributor_config(
+    deps: Deps,
+    distributor_contract: Addr,
+) -> StdResult<DistributorConfigResponse> {
+    deps.querier.query(&QueryRequest::Wasm(WasmQuery::Smart {
+        contract_addr: distributor_contract.to_string(),
+        msg: to_binary(&DistributorQueryMsg::Config {})?,
+    }))
+}
+
+pub fn query_token_symbol(deps: Deps, token_addr: Addr) -> StdResult<String> {
+    let token_info: TokenInfoResponse =
         deps.querier.query(&QueryRequest::Wasm(WasmQuery::Smart {
-            contract_addr: overseer_contract.to_string(),
-            msg: to_binary(&OverseerQueryMsg::Config {})?,
+            contract_addr: token_addr.to_string(),
+            msg: to_binary(&Cw20QueryMsg::TokenInfo {})?,
         }))?;
 
-    Ok(overseer_config.target_deposit_rate)
+    Ok(token_info.symbol)
 }
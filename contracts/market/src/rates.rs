@@ -0,0 +1,209 @@
+use cosmwasm_bignumber::{Decimal256, Uint256};
+use cosmwasm_std::{Addr, Deps, Env, StdResult};
+
+use moneymarket::market::{
+    BorrowerRateResponse, Compounding, NetDepositRateResponse, RateSpreadResponse, RatesResponse,
+};
+use moneymarket::querier::query_supply;
+
+use crate::borrow::compute_interest;
+use crate::deposit::compute_exchange_rate_raw;
+use crate::querier::{cached_market_borrow_rate, query_target_deposit_rate};
+use crate::stable::query_stable_balance;
+use crate::state::{read_borrower_info, read_config, read_state, BorrowerInfo, Config, State};
+
+pub const BLOCKS_PER_YEAR: u64 = 4656810;
+const DAYS_PER_YEAR: u64 = 365;
+
+// Taylor series terms drop below the decimal precision well
+// before this for any realistic annual rate
+const MAX_EXP_TERMS: u64 = 100;
+
+pub fn query_rates(
+    deps: Deps,
+    env: Env,
+    block_height: Option<u64>,
+    compounding: Compounding,
+) -> StdResult<RatesResponse> {
+    let config: Config = read_config(deps.storage)?;
+    let (borrow_rate, _, deposit_rate) =
+        current_rates(deps, &config, block_height.unwrap_or(env.block.height))?;
+
+    Ok(RatesResponse {
+        borrow_rate,
+        deposit_rate,
+        annual_borrow_rate: annualize(borrow_rate, config.blocks_per_year, &compounding),
+        annual_deposit_rate: annualize(deposit_rate, config.blocks_per_year, &compounding),
+    })
+}
+
+/// The reserve factor is the share of the interest paid
+/// over deposits that goes to reserves instead of depositors
+pub fn query_net_deposit_rate(
+    deps: Deps,
+    env: Env,
+    block_height: Option<u64>,
+) -> StdResult<NetDepositRateResponse> {
+    let config: Config = read_config(deps.storage)?;
+    let (_, gross_deposit_rate, net_deposit_rate) =
+        current_rates(deps, &config, block_height.unwrap_or(env.block.height))?;
+
+    let reserve_factor = if gross_deposit_rate.is_zero() {
+        Decimal256::zero()
+    } else {
+        (gross_deposit_rate - net_deposit_rate) / gross_deposit_rate
+    };
+
+    Ok(NetDepositRateResponse {
+        gross_deposit_rate,
+        reserve_factor,
+        net_deposit_rate,
+    })
+}
+
+/// The deposit rate can only exceed the borrow rate through fixed-rate
+/// interest, in which case there is no spread
+pub fn query_rate_spread(
+    deps: Deps,
+    env: Env,
+    block_height: Option<u64>,
+) -> StdResult<RateSpreadResponse> {
+    let rates = query_rates(deps, env, block_height, Compounding::Simple)?;
+    let spread = if rates.borrow_rate > rates.deposit_rate {
+        rates.borrow_rate - rates.deposit_rate
+    } else {
+        Decimal256::zero()
+    };
+
+    Ok(RateSpreadResponse {
+        borrow_rate: rates.borrow_rate,
+        deposit_rate: rates.deposit_rate,
+        spread,
+    })
+}
+
+/// Rate the borrower's loan accrues at: the locked rate of a fixed-rate
+/// position, zero within the grace period, or the global borrow rate
+pub fn query_borrower_rate(
+    deps: Deps,
+    env: Env,
+    borrower: Addr,
+) -> StdResult<BorrowerRateResponse> {
+    let liability: BorrowerInfo = read_borrower_info(
+        deps.storage,
+        &deps.api.addr_canonicalize(borrower.as_str())?,
+    );
+
+    let config: Config = read_config(deps.storage)?;
+    let rate = if liability.interest_free_until > env.block.height {
+        Decimal256::zero()
+    } else if let Some(fixed_rate) = liability.fixed_rate {
+        fixed_rate
+    } else {
+        let mut state: State = read_state(deps.storage)?;
+        compute_interest(deps, &config, &mut state, env.block.height, None)?;
+
+        let balance: Uint256 = query_stable_balance(deps, &config)?;
+        query_current_borrow_rate(deps, &config, &mut state, env.block.height, balance)?
+    };
+
+    Ok(BorrowerRateResponse {
+        borrower: borrower.to_string(),
+        rate,
+        annual_rate: annualize(rate, config.blocks_per_year, &Compounding::Simple),
+    })
+}
+
+/// Per block borrow rate at `block_height`, with the deposit rate before
+/// and after the excess over the target deposit rate goes to reserves
+fn current_rates(
+    deps: Deps,
+    config: &Config,
+    block_height: u64,
+) -> StdResult<(Decimal256, Decimal256, Decimal256)> {
+    let mut state: State = read_state(deps.storage)?;
+    compute_interest(deps, config, &mut state, block_height, None)?;
+
+    let aterra_supply = query_supply(deps, deps.api.addr_humanize(&config.aterra_contract)?)?;
+    let balance: Uint256 = query_stable_balance(deps, config)?;
+
+    let borrow_rate = query_current_borrow_rate(deps, config, &mut state, block_height, balance)?;
+    let target_deposit_rate =
+        query_target_deposit_rate(deps, deps.api.addr_humanize(&config.overseer_contract)?)?;
+
+    // Depositors earn the interest paid by variable and fixed-rate positions,
+    // up to the target deposit rate; the excess goes to reserves
+    let deposits = Decimal256::from_uint256(aterra_supply)
+        * compute_exchange_rate_raw(&state, aterra_supply, balance);
+    let gross_deposit_rate = if deposits.is_zero() {
+        Decimal256::zero()
+    } else {
+        (state.total_liabilities * borrow_rate + state.fixed_interest_per_block) / deposits
+    };
+
+    Ok((
+        borrow_rate,
+        gross_deposit_rate,
+        std::cmp::min(gross_deposit_rate, target_deposit_rate),
+    ))
+}
+
+/// The borrow rate accrual would use at `block_height`, cached or not
+fn query_current_borrow_rate(
+    deps: Deps,
+    config: &Config,
+    state: &mut State,
+    block_height: u64,
+    balance: Uint256,
+) -> StdResult<Decimal256> {
+    Ok(cached_market_borrow_rate(deps, config, state, block_height, balance)?.0)
+}
+
+/// Convert a per block rate into an annual rate
+/// * Simple: rate * blocks_per_year
+/// * Daily: (1 + simple / 365)^365 - 1
+/// * Continuous: e^simple - 1
+pub fn annualize(rate: Decimal256, blocks_per_year: u64, compounding: &Compounding) -> Decimal256 {
+    let simple = rate * Decimal256::from_uint256(blocks_per_year);
+    match compounding {
+        Compounding::Simple => simple,
+        Compounding::Daily => {
+            pow(
+                Decimal256::one() + simple / Decimal256::from_uint256(DAYS_PER_YEAR),
+                DAYS_PER_YEAR,
+            ) - Decimal256::one()
+        }
+        Compounding::Continuous => exp(simple) - Decimal256::one(),
+    }
+}
+
+fn pow(base: Decimal256, exponent: u64) -> Decimal256 {
+    let mut result = Decimal256::one();
+    let mut base = base;
+    let mut exponent = exponent;
+    while exponent > 0 {
+        if exponent % 2 == 1 {
+            result = result * base;
+        }
+
+        base = base * base;
+        exponent /= 2;
+    }
+
+    result
+}
+
+fn exp(x: Decimal256) -> Decimal256 {
+    let mut sum = Decimal256::one();
+    let mut term = Decimal256::one();
+    for n in 1..MAX_EXP_TERMS {
+        term = term * x / Decimal256::from_uint256(n);
+        if term.is_zero() {
+            break;
+        }
+
+        sum += term;
+    }
+
+    sum
+}
@@ -0,0 +1,32 @@
+use cosmwasm_bignumber::{Decimal256, Uint256};
+use cosmwasm_std::{Deps, StdResult};
+
+use moneymarket::querier::query_supply;
+
+use crate::deposit::compute_exchange_rate_raw;
+use crate::stable::query_stable_balance;
+use crate::state::{Config, State};
+
+/// Market balances queried once per entrypoint and shared by the interest
+/// accrual and exchange rate computations of that entrypoint.
+/// Liabilities and reserves change as interest accrues,
+/// so they are always read from the `State` being updated
+pub(crate) struct MarketSnapshot {
+    /// Stable balance, excluding the stable sent along with the message
+    pub balance: Uint256,
+    pub aterra_supply: Uint256,
+}
+
+impl MarketSnapshot {
+    pub fn load(deps: Deps, config: &Config, received_amount: Option<Uint256>) -> StdResult<Self> {
+        Ok(MarketSnapshot {
+            balance: query_stable_balance(deps, config)?
+                - received_amount.unwrap_or_else(Uint256::zero),
+            aterra_supply: query_supply(deps, deps.api.addr_humanize(&config.aterra_contract)?)?,
+        })
+    }
+
+    pub fn exchange_rate(&self, state: &State) -> Decimal256 {
+        compute_exchange_rate_raw(state, self.aterra_supply, self.balance)
+    }
+}
@@ -0,0 +1,174 @@
+use cosmwasm_bignumber::Uint256;
+use cosmwasm_std::{
+    from_binary, to_binary, Addr, BankMsg, Coin, CosmosMsg, Deps, DepsMut, MessageInfo, Response,
+    StdResult, Uint128, WasmMsg,
+};
+use cw20::Cw20ExecuteMsg;
+
+use moneymarket::market::TaxAmountResponse;
+use moneymarket::querier::{
+    compute_tax, query_balance, query_tax_rate_and_cap, query_token_balance,
+};
+
+use crate::error::ContractError;
+use crate::state::{read_config, read_state, store_state, Config, State};
+
+// The stable is either a native coin, where `stable_denom` is its denom,
+// or a cw20 token when `stable_cw20` is set, where `stable_denom` is
+// the token contract address
+
+/// Stable amount sent along with a native message;
+/// always zero for a cw20 stable, which comes in through receive_cw20.
+/// Native funds are credited by the bank module before execution
+/// and carry no transfer fee, so the sent amount is the received one.
+/// Any other coin would be stuck in the contract, so it is rejected
+pub fn stable_funds(config: &Config, info: &MessageInfo) -> Result<Uint256, ContractError> {
+    if let Some(coin) = info
+        .funds
+        .iter()
+        .find(|c| config.stable_cw20 || c.denom != config.stable_denom)
+    {
+        return Err(ContractError::ExtraFundsSent {
+            denom: coin.denom.to_string(),
+        });
+    }
+
+    Ok(info
+        .funds
+        .iter()
+        .find(|c| c.denom == config.stable_denom)
+        .map(|c| Uint256::from(c.amount))
+        .unwrap_or_else(Uint256::zero))
+}
+
+/// Stable a cw20 hook brought in, measured as the balance above the one
+/// the market tracked, so a fee-on-transfer stable credits the net amount.
+/// Capped at the sent amount, so stable transferred without a hook
+/// is not credited to the next sender
+pub fn received_stable_cw20(
+    deps: Deps,
+    config: &Config,
+    sent_amount: Uint128,
+) -> StdResult<Uint256> {
+    let state: State = read_state(deps.storage)?;
+    let balance = query_stable_balance(deps, config)?;
+    let received = if balance > state.stable_cw20_balance {
+        balance - state.stable_cw20_balance
+    } else {
+        Uint256::zero()
+    };
+
+    Ok(std::cmp::min(received, Uint256::from(sent_amount)))
+}
+
+/// Track the cw20 stable balance left once the stable transfers in `res`
+/// settle; a no-op for a native stable
+pub fn track_stable_cw20_balance(deps: DepsMut, res: &Response) -> StdResult<()> {
+    let config: Config = read_config(deps.storage)?;
+    if !config.stable_cw20 {
+        return Ok(());
+    }
+
+    let sent_amount = res
+        .messages
+        .iter()
+        .filter_map(|sub_msg| match &sub_msg.msg {
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr, msg, ..
+            }) if *contract_addr == config.stable_denom => from_binary(msg).ok(),
+            _ => None,
+        })
+        .fold(Uint256::zero(), |sum, msg| match msg {
+            Cw20ExecuteMsg::Transfer { amount, .. } => sum + Uint256::from(amount),
+            _ => sum,
+        });
+
+    let balance = query_stable_balance(deps.as_ref(), &config)?;
+    let mut state: State = read_state(deps.storage)?;
+    state.stable_cw20_balance = if balance > sent_amount {
+        balance - sent_amount
+    } else {
+        Uint256::zero()
+    };
+
+    store_state(deps.storage, &state)
+}
+
+pub fn query_stable_balance(deps: Deps, config: &Config) -> StdResult<Uint256> {
+    let contract_addr = deps.api.addr_humanize(&config.contract_addr)?;
+    if config.stable_cw20 {
+        query_token_balance(deps, Addr::unchecked(&config.stable_denom), contract_addr)
+    } else {
+        query_balance(deps, contract_addr, config.stable_denom.to_string())
+    }
+}
+
+/// Native transfers are net of tax; cw20 transfers carry none
+pub fn stable_transfer_msg(
+    deps: Deps,
+    config: &Config,
+    recipient: String,
+    amount: Uint256,
+) -> StdResult<CosmosMsg> {
+    if config.stable_cw20 {
+        return Ok(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: config.stable_denom.to_string(),
+            funds: vec![],
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient,
+                amount: amount.into(),
+            })?,
+        }));
+    }
+
+    Ok(CosmosMsg::Bank(BankMsg::Send {
+        to_address: recipient,
+        amount: vec![deduct_stable_tax(
+            deps,
+            config,
+            Coin {
+                denom: config.stable_denom.to_string(),
+                amount: amount.into(),
+            },
+        )?],
+    }))
+}
+
+/// deduct_tax with the tax clamped to `tax_cap_override`, when set
+pub fn deduct_stable_tax(deps: Deps, config: &Config, coin: Coin) -> StdResult<Coin> {
+    let mut tax_amount = compute_tax(deps, &coin)?;
+    if let Some(tax_cap_override) = config.tax_cap_override {
+        tax_amount = std::cmp::min(tax_amount, Uint256::from(tax_cap_override));
+    }
+
+    Ok(Coin {
+        denom: coin.denom,
+        amount: (Uint256::from(coin.amount) - tax_amount).into(),
+    })
+}
+
+/// Tax charged on top to whoever sends `amount` of stable to the market
+pub fn sender_tax(deps: Deps, config: &Config, amount: Uint256) -> StdResult<Uint256> {
+    if config.stable_cw20 {
+        return Ok(Uint256::zero());
+    }
+
+    let (tax_rate, tax_cap) = query_tax_rate_and_cap(deps, config.stable_denom.to_string())?;
+    Ok(std::cmp::min(amount * tax_rate, tax_cap))
+}
+
+/// Tax `stable_transfer_msg` deducts when sending `amount`
+pub fn query_tax_amount(deps: Deps, amount: Uint256) -> StdResult<TaxAmountResponse> {
+    let config: Config = read_config(deps.storage)?;
+    let tax_amount = if config.stable_cw20 {
+        Uint256::zero()
+    } else {
+        let coin = Coin {
+            denom: config.stable_denom.to_string(),
+            amount: amount.into(),
+        };
+        amount - Uint256::from(deduct_stable_tax(deps, &config, coin)?.amount)
+    };
+
+    Ok(TaxAmountResponse { tax_amount })
+}
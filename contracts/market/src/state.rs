@@ -1,16 +1,39 @@
 use schemars::JsonSchema;
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
 
 use cosmwasm_bignumber::{Decimal256, Uint256};
-use cosmwasm_std::{CanonicalAddr, Deps, Order, StdResult, Storage};
-use cosmwasm_storage::{bucket, bucket_read, ReadonlyBucket, ReadonlySingleton, Singleton};
+use cosmwasm_std::{Binary, CanonicalAddr, Deps, Order, StdResult, Storage, Uint128};
+use cosmwasm_storage::{bucket, bucket_read, Bucket, ReadonlyBucket, ReadonlySingleton, Singleton};
 
-use moneymarket::market::BorrowerInfoResponse;
+use moneymarket::market::{
+    BorrowerInfoResponse, ConfigParam, LiabilitySnapshot, ProposalStatus, ReserveTransfer,
+    RewardIndexSnapshot, RewardTenureTier, RoundingMode,
+};
 
 pub const KEY_CONFIG: &[u8] = b"config";
 pub const KEY_STATE: &[u8] = b"state";
+const KEY_REWARD_INDEX_HISTORY_COUNT: &[u8] = b"reward_index_history_count";
+const KEY_PENDING_CONFIG_CHANGES: &[u8] = b"pending_config_changes";
+const KEY_PROPOSAL_COUNT: &[u8] = b"proposal_count";
+const KEY_LOCKED_DEPOSIT_COUNT: &[u8] = b"locked_deposit_count";
+const KEY_LIABILITY_HISTORY_COUNT: &[u8] = b"liability_history_count";
+const KEY_RESERVE_TRANSFER_HISTORY_COUNT: &[u8] = b"reserve_transfer_history_count";
 
 const PREFIX_LIABILITY: &[u8] = b"liability";
+const PREFIX_REFERRAL_VOLUME: &[u8] = b"referral_volume";
+const PREFIX_LIQUIDATION_CALLBACK: &[u8] = b"liquidation_callback";
+const PREFIX_REWARD_INDEX_HISTORY: &[u8] = b"reward_index_history";
+const PREFIX_LAST_DEPOSIT: &[u8] = b"last_deposit";
+const PREFIX_PROPOSAL: &[u8] = b"proposal";
+const PREFIX_PROPOSAL_VOTE: &[u8] = b"proposal_vote";
+const PREFIX_LOCKED_DEPOSIT: &[u8] = b"locked_deposit";
+const PREFIX_LIABILITY_HISTORY: &[u8] = b"liability_history";
+const PREFIX_BORROW_DELEGATE: &[u8] = b"borrow_delegate";
+const PREFIX_RESERVE_TRANSFER_HISTORY: &[u8] = b"reserve_transfer_history";
+
+/// # of snapshots kept by each history ring buffer
+pub const HISTORY_CAPACITY: u64 = 100;
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct Config {
@@ -24,6 +47,92 @@ pub struct Config {
     pub distributor_contract: CanonicalAddr,
     pub stable_denom: String,
     pub max_borrow_factor: Decimal256,
+    /// # of blocks a newly opened position accrues no interest
+    pub borrow_grace_period: u64,
+    /// Caller allowed to run epoch operations in addition to the overseer
+    pub epoch_caller: Option<CanonicalAddr>,
+    /// # of blocks a borrower must wait after a borrow to borrow again
+    pub borrow_cooldown: u64,
+    /// # of blocks after an address' latest deposit before it can redeem
+    pub redeem_lockup: u64,
+    /// Whether `stable_denom` is the address of a cw20 stable
+    pub stable_cw20: bool,
+    /// Caps the tax deducted from stable transfers below the chain's tax cap
+    pub tax_cap_override: Option<Uint128>,
+    /// Minimum share of total deposits a borrow must leave available
+    pub min_liquidity_ratio: Decimal256,
+    /// Share of the stable recovered by a liquidation kept as reserves
+    pub liquidation_fee_rate: Decimal256,
+    /// Max page size of the BorrowerInfos query
+    pub max_borrower_infos_limit: u32,
+    /// Interest model queried when `interest_model` errors
+    pub fallback_interest_model: Option<CanonicalAddr>,
+    /// Borrow rate used when both interest models error
+    pub fallback_borrow_rate: Option<Decimal256>,
+    /// How much `base_borrow_factor` tightens with utilization;
+    /// zero keeps `max_borrow_factor` static
+    pub utilization_sensitivity: Decimal256,
+    /// Reward multipliers by position tenure, in ascending order; a
+    /// position earns its share of the emission scaled by its multiplier
+    /// over the top tier's, one for positions below the first tier
+    pub reward_tenure_tiers: Vec<RewardTenureTier>,
+    /// Reserves sent to the collector per transfer; the excess goes to
+    /// `reserve_excess_recipient`, when set
+    pub reserve_target: Option<Uint256>,
+    /// Receives the reserves above `reserve_target` or `max_reserves`
+    pub reserve_excess_recipient: Option<CanonicalAddr>,
+    /// Cap on the reserves left accumulating when the balance cannot cover
+    /// a transfer; the overflow goes to `reserve_excess_recipient`
+    pub max_reserves: Option<Uint256>,
+    /// Cap on a single borrow, regardless of the borrower's limit
+    pub max_borrow_per_tx: Option<Uint256>,
+    /// Addresses voting on parameter proposals
+    pub guardians: Vec<CanonicalAddr>,
+    /// # of guardian approvals a parameter proposal needs to pass
+    pub guardian_threshold: u32,
+    /// Pauses reward claims alone, e.g. while the distributor migrates
+    pub claims_paused: bool,
+    /// # of blocks a queried borrow rate is reused for;
+    /// zero queries the interest model every time
+    pub rate_refresh_interval: u64,
+    /// Floor on the anc_emission_rate queried from the distribution model
+    pub min_anc_emission_rate: Decimal256,
+    /// Fraction of a partially filled redemption withheld into reserves when
+    /// liquidity cannot cover it in full; None rejects such redemptions
+    pub low_liquidity_haircut: Option<Decimal256>,
+    /// Cap on the total borrowed across all borrowers in a block
+    pub max_borrow_per_block: Option<Uint256>,
+    /// Share of the yield a locked deposit earned over its term
+    /// added on its redemption. The reward index only carries ANC to
+    /// borrowers, and depositors earn through the exchange rate, so the
+    /// boost is paid in stable from the reserves, the only stable not
+    /// owed to depositors, and never more than the reserves hold
+    pub locked_deposit_boost: Decimal256,
+    /// Whether a redemption worth less than one unit of stable
+    /// burns the aterra for nothing, rather than failing
+    pub burn_redeem_dust: bool,
+    /// Whether epoch operations fail, rather than only warn,
+    /// when the aterra supply exceeds the tracked supply
+    pub strict_supply_check: bool,
+    /// # of blocks per year, for annualizing the per block rates
+    pub blocks_per_year: u64,
+    /// Cap on the total deposits, in stable, a deposit can raise them to
+    pub max_total_deposits: Option<Uint256>,
+    /// Whether a redemption in the block interest last accrued at reuses
+    /// the exchange rate stored then, rather than recomputing it
+    pub redeem_fast_path: bool,
+    /// Rounding of the liability and interest index growth on accrual,
+    /// and of loans settled against the grown index
+    pub rounding_mode: RoundingMode,
+    /// Fill a borrow the market's cash cannot fully cover up to the
+    /// available stable, rather than failing it with InsufficientLiquidity
+    pub partial_fill_borrows: bool,
+    /// Staking contracts deposit_and_stake may send aterra to; each must
+    /// credit the staker named in the hook msg rather than the market
+    pub staking_contracts: Vec<CanonicalAddr>,
+    /// Borrow factor the utilization curve tightens from, capped
+    /// at `max_borrow_factor`; starts out equal to it
+    pub base_borrow_factor: Decimal256,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -37,6 +146,45 @@ pub struct State {
     pub anc_emission_rate: Decimal256,
     pub prev_aterra_supply: Uint256,
     pub prev_exchange_rate: Decimal256,
+    /// Liabilities of fixed-rate positions, not tracked by the global index
+    #[serde(default)]
+    pub total_fixed_liabilities: Decimal256,
+    /// Sum of the interest fixed-rate positions accrue per block
+    #[serde(default)]
+    pub fixed_interest_per_block: Decimal256,
+    /// aTerra held by the market itself, the initial seed
+    /// and protocol-owned deposits
+    #[serde(default)]
+    pub protocol_owned_aterra: Uint256,
+    /// Reserves of the latest collector transfer; cleared once it succeeds,
+    /// or restored to `total_reserves` for the next epoch when it fails
+    #[serde(default)]
+    pub pending_reserve_transfer: Uint256,
+    /// Borrow rate last queried from the interest model,
+    /// reused for `Config.rate_refresh_interval` blocks
+    #[serde(default)]
+    pub cached_borrow_rate: Option<Decimal256>,
+    #[serde(default)]
+    pub borrow_rate_cached_at: u64,
+    /// Highest utilization seen at an accrual since the last reset
+    #[serde(default)]
+    pub peak_utilization: Decimal256,
+    /// Total borrowed at `borrowed_at_block`, tracked
+    /// while `Config.max_borrow_per_block` is set
+    #[serde(default)]
+    pub borrowed_this_block: Uint256,
+    #[serde(default)]
+    pub borrowed_at_block: u64,
+    /// Reserves the collector has received over the market's lifetime
+    #[serde(default)]
+    pub cumulative_reserves_collected: Uint256,
+    /// cw20 stable balance the market holds once the transfers of its
+    /// last execution settle; always zero for a native stable
+    #[serde(default)]
+    pub stable_cw20_balance: Uint256,
+    /// Borrowers with an open loan, counted as positions open and close
+    #[serde(default)]
+    pub borrower_count: u64,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -45,6 +193,26 @@ pub struct BorrowerInfo {
     pub reward_index: Decimal256,
     pub loan_amount: Uint256,
     pub pending_rewards: Decimal256,
+    // Fields added after launch default to zero
+    // when loading positions stored before them
+    /// Block height until which accrued interest is waived
+    #[serde(default)]
+    pub interest_free_until: u64,
+    /// Block height interest was last applied to the loan
+    #[serde(default)]
+    pub last_interest_updated: u64,
+    /// Per block borrow rate locked when a fixed-rate position is opened
+    #[serde(default)]
+    pub fixed_rate: Option<Decimal256>,
+    /// Block height of the latest borrow
+    #[serde(default)]
+    pub last_borrowed: u64,
+    /// Block height the position was opened at
+    #[serde(default)]
+    pub opened_at: u64,
+    /// Block height the position's rewards were last settled at
+    #[serde(default)]
+    pub reward_updated_at: u64,
 }
 
 pub fn store_config(storage: &mut dyn Storage, data: &Config) -> StdResult<()> {
@@ -63,6 +231,110 @@ pub fn read_state(storage: &dyn Storage) -> StdResult<State> {
     ReadonlySingleton::new(storage, KEY_STATE).load()
 }
 
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PendingConfigChange {
+    pub owner_addr: Option<CanonicalAddr>,
+    pub interest_model: Option<CanonicalAddr>,
+    pub max_borrow_factor: Option<Decimal256>,
+    /// Block height from which the change can be applied
+    pub eta: u64,
+}
+
+pub fn store_pending_config_changes(
+    storage: &mut dyn Storage,
+    data: &[PendingConfigChange],
+) -> StdResult<()> {
+    Singleton::new(storage, KEY_PENDING_CONFIG_CHANGES).save(&data.to_vec())
+}
+
+pub fn read_pending_config_changes(storage: &dyn Storage) -> StdResult<Vec<PendingConfigChange>> {
+    Ok(ReadonlySingleton::new(storage, KEY_PENDING_CONFIG_CHANGES)
+        .may_load()?
+        .unwrap_or_default())
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Proposal {
+    pub param: ConfigParam,
+    pub value: String,
+    pub proposer: CanonicalAddr,
+    pub approvals: u32,
+    pub rejections: u32,
+    pub status: ProposalStatus,
+}
+
+/// Stores a new proposal under the next id, starting from 1
+pub fn push_proposal(storage: &mut dyn Storage, proposal: &Proposal) -> StdResult<u64> {
+    let id: u64 = ReadonlySingleton::new(storage, KEY_PROPOSAL_COUNT)
+        .may_load()?
+        .unwrap_or(0)
+        + 1;
+
+    store_proposal(storage, id, proposal)?;
+    Singleton::new(storage, KEY_PROPOSAL_COUNT).save(&id)?;
+    Ok(id)
+}
+
+pub fn store_proposal(storage: &mut dyn Storage, id: u64, proposal: &Proposal) -> StdResult<()> {
+    bucket(storage, PREFIX_PROPOSAL).save(&id.to_be_bytes(), proposal)
+}
+
+pub fn read_proposal(storage: &dyn Storage, id: u64) -> StdResult<Proposal> {
+    bucket_read(storage, PREFIX_PROPOSAL).load(&id.to_be_bytes())
+}
+
+pub fn store_proposal_vote(
+    storage: &mut dyn Storage,
+    id: u64,
+    voter: &CanonicalAddr,
+    approve: bool,
+) -> StdResult<()> {
+    Bucket::multilevel(storage, &[PREFIX_PROPOSAL_VOTE, &id.to_be_bytes()])
+        .save(voter.as_slice(), &approve)
+}
+
+pub fn read_proposal_vote(
+    storage: &dyn Storage,
+    id: u64,
+    voter: &CanonicalAddr,
+) -> StdResult<Option<bool>> {
+    ReadonlyBucket::multilevel(storage, &[PREFIX_PROPOSAL_VOTE, &id.to_be_bytes()])
+        .may_load(voter.as_slice())
+}
+
+/// Deposit whose aterra the market holds until `unlock_at`
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct LockedDeposit {
+    pub owner: CanonicalAddr,
+    pub aterra_amount: Uint256,
+    /// Stable deposited, which the boost is computed against
+    pub principal: Uint256,
+    pub unlock_at: u64,
+}
+
+/// Stores a new locked deposit under the next id, starting from 1
+pub fn push_locked_deposit(
+    storage: &mut dyn Storage,
+    locked_deposit: &LockedDeposit,
+) -> StdResult<u64> {
+    let id: u64 = ReadonlySingleton::new(storage, KEY_LOCKED_DEPOSIT_COUNT)
+        .may_load()?
+        .unwrap_or(0)
+        + 1;
+
+    bucket(storage, PREFIX_LOCKED_DEPOSIT).save(&id.to_be_bytes(), locked_deposit)?;
+    Singleton::new(storage, KEY_LOCKED_DEPOSIT_COUNT).save(&id)?;
+    Ok(id)
+}
+
+pub fn read_locked_deposit(storage: &dyn Storage, id: u64) -> StdResult<LockedDeposit> {
+    bucket_read(storage, PREFIX_LOCKED_DEPOSIT).load(&id.to_be_bytes())
+}
+
+pub fn remove_locked_deposit(storage: &mut dyn Storage, id: u64) {
+    bucket::<LockedDeposit>(storage, PREFIX_LOCKED_DEPOSIT).remove(&id.to_be_bytes())
+}
+
 pub fn store_borrower_info(
     storage: &mut dyn Storage,
     borrower: &CanonicalAddr,
@@ -71,6 +343,14 @@ pub fn store_borrower_info(
     bucket(storage, PREFIX_LIABILITY).save(borrower.as_slice(), liability)
 }
 
+pub fn read_all_borrower_infos(storage: &dyn Storage) -> StdResult<Vec<BorrowerInfo>> {
+    let liability_bucket: ReadonlyBucket<BorrowerInfo> = bucket_read(storage, PREFIX_LIABILITY);
+    liability_bucket
+        .range(None, None, Order::Ascending)
+        .map(|elem| Ok(elem?.1))
+        .collect()
+}
+
 pub fn read_borrower_info(storage: &dyn Storage, borrower: &CanonicalAddr) -> BorrowerInfo {
     match bucket_read(storage, PREFIX_LIABILITY).load(borrower.as_slice()) {
         Ok(v) => v,
@@ -79,22 +359,240 @@ pub fn read_borrower_info(storage: &dyn Storage, borrower: &CanonicalAddr) -> Bo
             reward_index: Decimal256::zero(),
             loan_amount: Uint256::zero(),
             pending_rewards: Decimal256::zero(),
+            interest_free_until: 0,
+            last_interest_updated: 0,
+            fixed_rate: None,
+            last_borrowed: 0,
+            opened_at: 0,
+            reward_updated_at: 0,
         },
     }
 }
 
+pub fn store_referral_volume(
+    storage: &mut dyn Storage,
+    referrer: &CanonicalAddr,
+    volume: &Uint256,
+) -> StdResult<()> {
+    bucket(storage, PREFIX_REFERRAL_VOLUME).save(referrer.as_slice(), volume)
+}
+
+pub fn read_referral_volume(storage: &dyn Storage, referrer: &CanonicalAddr) -> Uint256 {
+    bucket_read(storage, PREFIX_REFERRAL_VOLUME)
+        .load(referrer.as_slice())
+        .unwrap_or_else(|_| Uint256::zero())
+}
+
+pub fn store_last_deposit(
+    storage: &mut dyn Storage,
+    depositor: &CanonicalAddr,
+    block_height: u64,
+) -> StdResult<()> {
+    bucket(storage, PREFIX_LAST_DEPOSIT).save(depositor.as_slice(), &block_height)
+}
+
+pub fn read_last_deposit(storage: &dyn Storage, depositor: &CanonicalAddr) -> u64 {
+    bucket_read(storage, PREFIX_LAST_DEPOSIT)
+        .load(depositor.as_slice())
+        .unwrap_or(0)
+}
+
+pub fn store_liquidation_callback(
+    storage: &mut dyn Storage,
+    borrower: &CanonicalAddr,
+    msg: &Binary,
+) -> StdResult<()> {
+    bucket(storage, PREFIX_LIQUIDATION_CALLBACK).save(borrower.as_slice(), msg)
+}
+
+pub fn remove_liquidation_callback(storage: &mut dyn Storage, borrower: &CanonicalAddr) {
+    bucket::<Binary>(storage, PREFIX_LIQUIDATION_CALLBACK).remove(borrower.as_slice())
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct BorrowDelegate {
+    pub delegate: CanonicalAddr,
+    /// Amount the delegate can still borrow on the borrower's behalf
+    pub limit: Uint256,
+}
+
+pub fn store_borrow_delegate(
+    storage: &mut dyn Storage,
+    borrower: &CanonicalAddr,
+    delegate: &BorrowDelegate,
+) -> StdResult<()> {
+    bucket(storage, PREFIX_BORROW_DELEGATE).save(borrower.as_slice(), delegate)
+}
+
+pub fn remove_borrow_delegate(storage: &mut dyn Storage, borrower: &CanonicalAddr) {
+    bucket::<BorrowDelegate>(storage, PREFIX_BORROW_DELEGATE).remove(borrower.as_slice())
+}
+
+pub fn read_borrow_delegate(
+    storage: &dyn Storage,
+    borrower: &CanonicalAddr,
+) -> Option<BorrowDelegate> {
+    bucket_read(storage, PREFIX_BORROW_DELEGATE)
+        .may_load(borrower.as_slice())
+        .unwrap_or(None)
+}
+
+pub fn read_liquidation_callback(
+    storage: &dyn Storage,
+    borrower: &CanonicalAddr,
+) -> Option<Binary> {
+    bucket_read(storage, PREFIX_LIQUIDATION_CALLBACK)
+        .may_load(borrower.as_slice())
+        .unwrap_or(None)
+}
+
 // settings for pagination
-const MAX_LIMIT: u32 = 30;
+pub const MAX_LIMIT: u32 = 30;
 const DEFAULT_LIMIT: u32 = 10;
+/// Default page size of BorrowerInfos, low enough to stay within query gas;
+/// larger pages are capped by `Config.max_borrower_infos_limit`
+const DEFAULT_BORROWER_INFOS_LIMIT: u32 = 5;
+
+pub fn store_reward_index_snapshot(
+    storage: &mut dyn Storage,
+    snapshot: &RewardIndexSnapshot,
+) -> StdResult<()> {
+    push_history(
+        storage,
+        PREFIX_REWARD_INDEX_HISTORY,
+        KEY_REWARD_INDEX_HISTORY_COUNT,
+        snapshot,
+    )
+}
+
+pub fn read_reward_index_history(
+    storage: &dyn Storage,
+    limit: Option<u32>,
+) -> StdResult<Vec<RewardIndexSnapshot>> {
+    read_history(
+        storage,
+        PREFIX_REWARD_INDEX_HISTORY,
+        KEY_REWARD_INDEX_HISTORY_COUNT,
+        limit,
+    )
+}
+
+pub fn store_liability_snapshot(
+    storage: &mut dyn Storage,
+    snapshot: &LiabilitySnapshot,
+) -> StdResult<()> {
+    push_history(
+        storage,
+        PREFIX_LIABILITY_HISTORY,
+        KEY_LIABILITY_HISTORY_COUNT,
+        snapshot,
+    )
+}
+
+pub fn read_liability_history(
+    storage: &dyn Storage,
+    limit: Option<u32>,
+) -> StdResult<Vec<LiabilitySnapshot>> {
+    read_history(
+        storage,
+        PREFIX_LIABILITY_HISTORY,
+        KEY_LIABILITY_HISTORY_COUNT,
+        limit,
+    )
+}
+
+pub fn store_reserve_transfer(
+    storage: &mut dyn Storage,
+    transfer: &ReserveTransfer,
+) -> StdResult<()> {
+    push_history(
+        storage,
+        PREFIX_RESERVE_TRANSFER_HISTORY,
+        KEY_RESERVE_TRANSFER_HISTORY_COUNT,
+        transfer,
+    )
+}
+
+pub fn read_reserve_transfer_history(
+    storage: &dyn Storage,
+    limit: Option<u32>,
+) -> StdResult<Vec<ReserveTransfer>> {
+    read_history(
+        storage,
+        PREFIX_RESERVE_TRANSFER_HISTORY,
+        KEY_RESERVE_TRANSFER_HISTORY_COUNT,
+        limit,
+    )
+}
+
+// History ring buffers store the # of pushed entries under `count_key`
+// and the n-th entry at slot `n % HISTORY_CAPACITY`, overwriting the oldest
+fn push_history<T: Serialize + DeserializeOwned>(
+    storage: &mut dyn Storage,
+    prefix: &[u8],
+    count_key: &[u8],
+    item: &T,
+) -> StdResult<()> {
+    let count: u64 = ReadonlySingleton::new(storage, count_key)
+        .may_load()?
+        .unwrap_or(0);
+
+    bucket(storage, prefix).save(&(count % HISTORY_CAPACITY).to_be_bytes(), item)?;
+    Singleton::new(storage, count_key).save(&(count + 1))
+}
+
+// Returns up to the whole buffer, newest first; an absent count reads as empty
+fn read_history<T: Serialize + DeserializeOwned>(
+    storage: &dyn Storage,
+    prefix: &[u8],
+    count_key: &[u8],
+    limit: Option<u32>,
+) -> StdResult<Vec<T>> {
+    let count: u64 = ReadonlySingleton::new(storage, count_key)
+        .may_load()?
+        .unwrap_or(0);
+
+    let limit = (limit.unwrap_or(DEFAULT_LIMIT) as u64)
+        .min(count)
+        .min(HISTORY_CAPACITY);
+
+    let history_bucket: ReadonlyBucket<T> = bucket_read(storage, prefix);
+    (1..=limit)
+        .map(|i| history_bucket.load(&((count - i) % HISTORY_CAPACITY).to_be_bytes()))
+        .collect()
+}
+
 pub fn read_borrower_infos(
     deps: Deps,
     start_after: Option<CanonicalAddr>,
     limit: Option<u32>,
+    max_limit: u32,
 ) -> StdResult<Vec<BorrowerInfoResponse>> {
-    let liability_bucket: ReadonlyBucket<BorrowerInfo> =
-        bucket_read(deps.storage, PREFIX_LIABILITY);
+    read_borrower_info_page(deps.storage, start_after, limit, max_limit)?
+        .into_iter()
+        .map(|(k, v)| {
+            let borrower = deps.api.addr_humanize(&k)?.to_string();
+            Ok(BorrowerInfoResponse {
+                borrower,
+                interest_index: v.interest_index,
+                reward_index: v.reward_index,
+                loan_amount: v.loan_amount,
+                pending_rewards: v.pending_rewards,
+                fixed_rate: v.fixed_rate,
+            })
+        })
+        .collect()
+}
+
+pub fn read_borrower_info_page(
+    storage: &dyn Storage,
+    start_after: Option<CanonicalAddr>,
+    limit: Option<u32>,
+    max_limit: u32,
+) -> StdResult<Vec<(CanonicalAddr, BorrowerInfo)>> {
+    let liability_bucket: ReadonlyBucket<BorrowerInfo> = bucket_read(storage, PREFIX_LIABILITY);
 
-    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let limit = limit.unwrap_or(DEFAULT_BORROWER_INFOS_LIMIT).min(max_limit) as usize;
     let start = calc_range_start(start_after);
 
     liability_bucket
@@ -102,14 +600,7 @@ pub fn read_borrower_infos(
         .take(limit)
         .map(|elem| {
             let (k, v) = elem?;
-            let borrower = deps.api.addr_humanize(&CanonicalAddr::from(k))?.to_string();
-            Ok(BorrowerInfoResponse {
-                borrower,
-                interest_index: v.interest_index,
-                reward_index: v.reward_index,
-                loan_amount: v.loan_amount,
-                pending_rewards: v.pending_rewards,
-            })
+            Ok((CanonicalAddr::from(k), v))
         })
         .collect()
 }
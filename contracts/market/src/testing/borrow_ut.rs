@@ -1,9 +1,15 @@
-use crate::borrow::{compute_borrower_interest, compute_interest};
+use crate::borrow::{
+    compute_borrower_interest, compute_interest, compute_interest_raw, effective_max_borrow_factor,
+    mul_rounded,
+};
+use crate::rates::BLOCKS_PER_YEAR;
 use crate::state::{store_state, BorrowerInfo, Config, State};
 use crate::testing::mock_querier::mock_dependencies;
 use cosmwasm_bignumber::{Decimal256, Uint256};
 use cosmwasm_std::testing::{mock_env, MOCK_CONTRACT_ADDR};
 use cosmwasm_std::{Api, Coin, Uint128};
+use moneymarket::market::RoundingMode;
+use std::str::FromStr;
 
 #[test]
 fn proper_compute_borrower_interest() {
@@ -18,19 +24,43 @@ fn proper_compute_borrower_interest() {
         anc_emission_rate: Decimal256::one(),
         prev_aterra_supply: Uint256::zero(),
         prev_exchange_rate: Decimal256::one(),
+        total_fixed_liabilities: Decimal256::zero(),
+        fixed_interest_per_block: Decimal256::zero(),
+        protocol_owned_aterra: Uint256::zero(),
+        pending_reserve_transfer: Uint256::zero(),
+        cached_borrow_rate: None,
+        borrow_rate_cached_at: 0,
+        peak_utilization: Decimal256::zero(),
+        borrowed_this_block: Uint256::zero(),
+        borrowed_at_block: 0,
+        cumulative_reserves_collected: Uint256::zero(),
+        stable_cw20_balance: Uint256::zero(),
+        borrower_count: 0,
     };
     let mut liability1 = BorrowerInfo {
         interest_index: Decimal256::one(),
         reward_index: Decimal256::zero(),
         loan_amount: Uint256::zero(),
         pending_rewards: Decimal256::zero(),
+        interest_free_until: 0,
+        last_interest_updated: 0,
+        fixed_rate: None,
+        last_borrowed: 0,
+        opened_at: 0,
+        reward_updated_at: 0,
     };
-    compute_borrower_interest(&mock_state, &mut liability1);
+    compute_borrower_interest(&mock_state, &mut liability1, &RoundingMode::Floor);
     let liability2 = BorrowerInfo {
         interest_index: Decimal256::one(),
         reward_index: Decimal256::zero(),
         loan_amount: Uint256::zero(),
         pending_rewards: Decimal256::zero(),
+        interest_free_until: 0,
+        last_interest_updated: 0,
+        fixed_rate: None,
+        last_borrowed: 0,
+        opened_at: 0,
+        reward_updated_at: 0,
     };
     assert_eq!(liability1, liability2);
 
@@ -44,19 +74,43 @@ fn proper_compute_borrower_interest() {
         anc_emission_rate: Decimal256::zero(),
         prev_aterra_supply: Uint256::zero(),
         prev_exchange_rate: Decimal256::one(),
+        total_fixed_liabilities: Decimal256::zero(),
+        fixed_interest_per_block: Decimal256::zero(),
+        protocol_owned_aterra: Uint256::zero(),
+        pending_reserve_transfer: Uint256::zero(),
+        cached_borrow_rate: None,
+        borrow_rate_cached_at: 0,
+        peak_utilization: Decimal256::zero(),
+        borrowed_this_block: Uint256::zero(),
+        borrowed_at_block: 0,
+        cumulative_reserves_collected: Uint256::zero(),
+        stable_cw20_balance: Uint256::zero(),
+        borrower_count: 0,
     };
     let mut liability3 = BorrowerInfo {
         interest_index: Decimal256::from_uint256(4u128),
         reward_index: Decimal256::zero(),
         loan_amount: Uint256::from(80u128),
         pending_rewards: Decimal256::zero(),
+        interest_free_until: 0,
+        last_interest_updated: 0,
+        fixed_rate: None,
+        last_borrowed: 0,
+        opened_at: 0,
+        reward_updated_at: 0,
     };
-    compute_borrower_interest(&mock_state2, &mut liability3);
+    compute_borrower_interest(&mock_state2, &mut liability3, &RoundingMode::Floor);
     let liability4 = BorrowerInfo {
         interest_index: Decimal256::from_uint256(2u128),
         reward_index: Decimal256::zero(),
         loan_amount: Uint256::from(40u128),
         pending_rewards: Decimal256::zero(),
+        interest_free_until: 0,
+        last_interest_updated: 0,
+        fixed_rate: None,
+        last_borrowed: 0,
+        opened_at: 0,
+        reward_updated_at: 0,
     };
     assert_eq!(liability3, liability4);
 }
@@ -86,6 +140,40 @@ fn proper_compute_interest() {
         overseer_contract: deps.api.addr_canonicalize("overseer").unwrap(),
         stable_denom: "uusd".to_string(),
         max_borrow_factor: Decimal256::one(),
+        borrow_grace_period: 0,
+        epoch_caller: None,
+        borrow_cooldown: 0,
+        redeem_lockup: 0,
+        stable_cw20: false,
+        tax_cap_override: None,
+        min_liquidity_ratio: Decimal256::zero(),
+        liquidation_fee_rate: Decimal256::zero(),
+        max_borrower_infos_limit: 30,
+        fallback_interest_model: None,
+        fallback_borrow_rate: None,
+        utilization_sensitivity: Decimal256::zero(),
+        reward_tenure_tiers: vec![],
+        reserve_target: None,
+        reserve_excess_recipient: None,
+        max_reserves: None,
+        max_borrow_per_tx: None,
+        guardians: vec![],
+        guardian_threshold: 0,
+        claims_paused: false,
+        rate_refresh_interval: 0,
+        min_anc_emission_rate: Decimal256::zero(),
+        low_liquidity_haircut: None,
+        max_borrow_per_block: None,
+        locked_deposit_boost: Decimal256::zero(),
+        burn_redeem_dust: false,
+        strict_supply_check: false,
+        blocks_per_year: BLOCKS_PER_YEAR,
+        max_total_deposits: None,
+        redeem_fast_path: false,
+        rounding_mode: RoundingMode::Floor,
+        partial_fill_borrows: false,
+        staking_contracts: vec![],
+        base_borrow_factor: Decimal256::one(),
     };
 
     deps.querier
@@ -101,6 +189,18 @@ fn proper_compute_interest() {
         anc_emission_rate: Decimal256::one(),
         prev_aterra_supply: Uint256::zero(),
         prev_exchange_rate: Decimal256::one(),
+        total_fixed_liabilities: Decimal256::zero(),
+        fixed_interest_per_block: Decimal256::zero(),
+        protocol_owned_aterra: Uint256::zero(),
+        pending_reserve_transfer: Uint256::zero(),
+        cached_borrow_rate: None,
+        borrow_rate_cached_at: 0,
+        peak_utilization: Decimal256::zero(),
+        borrowed_this_block: Uint256::zero(),
+        borrowed_at_block: 0,
+        cumulative_reserves_collected: Uint256::zero(),
+        stable_cw20_balance: Uint256::zero(),
+        borrower_count: 0,
     };
     store_state(&mut deps.storage, &mock_state).unwrap();
 
@@ -126,6 +226,18 @@ fn proper_compute_interest() {
             anc_emission_rate: Decimal256::one(),
             prev_aterra_supply: Uint256::zero(),
             prev_exchange_rate: Decimal256::one(),
+            total_fixed_liabilities: Decimal256::zero(),
+            fixed_interest_per_block: Decimal256::zero(),
+            protocol_owned_aterra: Uint256::zero(),
+            pending_reserve_transfer: Uint256::zero(),
+            cached_borrow_rate: None,
+            borrow_rate_cached_at: 0,
+            peak_utilization: Decimal256::zero(),
+            borrowed_this_block: Uint256::zero(),
+            borrowed_at_block: 0,
+            cumulative_reserves_collected: Uint256::zero(),
+            stable_cw20_balance: Uint256::zero(),
+            borrower_count: 0,
         }
     );
 
@@ -151,6 +263,18 @@ fn proper_compute_interest() {
             anc_emission_rate: Decimal256::one(),
             prev_aterra_supply: Uint256::from(2000000u64),
             prev_exchange_rate: Decimal256::from_ratio(19995, 10000),
+            total_fixed_liabilities: Decimal256::zero(),
+            fixed_interest_per_block: Decimal256::zero(),
+            protocol_owned_aterra: Uint256::zero(),
+            pending_reserve_transfer: Uint256::zero(),
+            cached_borrow_rate: None,
+            borrow_rate_cached_at: 0,
+            peak_utilization: Decimal256::from_str("0.500125031257814453").unwrap(),
+            borrowed_this_block: Uint256::zero(),
+            borrowed_at_block: 0,
+            cumulative_reserves_collected: Uint256::zero(),
+            stable_cw20_balance: Uint256::zero(),
+            borrower_count: 0,
         }
     );
 
@@ -165,6 +289,18 @@ fn proper_compute_interest() {
         anc_emission_rate: Decimal256::one(),
         prev_aterra_supply: Uint256::from(2000000u128),
         prev_exchange_rate: Decimal256::one(),
+        total_fixed_liabilities: Decimal256::zero(),
+        fixed_interest_per_block: Decimal256::zero(),
+        protocol_owned_aterra: Uint256::zero(),
+        pending_reserve_transfer: Uint256::zero(),
+        cached_borrow_rate: None,
+        borrow_rate_cached_at: 0,
+        peak_utilization: Decimal256::zero(),
+        borrowed_this_block: Uint256::zero(),
+        borrowed_at_block: 0,
+        cumulative_reserves_collected: Uint256::zero(),
+        stable_cw20_balance: Uint256::zero(),
+        borrower_count: 0,
     };
     store_state(&mut deps.storage, &mock_state).unwrap();
 
@@ -200,6 +336,237 @@ fn proper_compute_interest() {
             anc_emission_rate: Decimal256::one(),
             prev_aterra_supply: Uint256::from(2000000u64),
             prev_exchange_rate: Decimal256::from_uint256(2u64),
+            total_fixed_liabilities: Decimal256::zero(),
+            fixed_interest_per_block: Decimal256::zero(),
+            protocol_owned_aterra: Uint256::zero(),
+            pending_reserve_transfer: Uint256::zero(),
+            cached_borrow_rate: None,
+            borrow_rate_cached_at: 0,
+            peak_utilization: Decimal256::zero(),
+            borrowed_this_block: Uint256::zero(),
+            borrowed_at_block: 0,
+            cumulative_reserves_collected: Uint256::zero(),
+            stable_cw20_balance: Uint256::zero(),
+            borrower_count: 0,
         }
     );
 }
+
+#[test]
+fn proper_effective_max_borrow_factor() {
+    let deps = mock_dependencies(&[]);
+    let mut mock_config = Config {
+        contract_addr: deps.api.addr_canonicalize(MOCK_CONTRACT_ADDR).unwrap(),
+        owner_addr: deps.api.addr_canonicalize("owner").unwrap(),
+        aterra_contract: deps.api.addr_canonicalize("AT-uusd").unwrap(),
+        interest_model: deps.api.addr_canonicalize("interest").unwrap(),
+        distribution_model: deps.api.addr_canonicalize("distribution").unwrap(),
+        distributor_contract: deps.api.addr_canonicalize("distributor").unwrap(),
+        collector_contract: deps.api.addr_canonicalize("collector").unwrap(),
+        overseer_contract: deps.api.addr_canonicalize("overseer").unwrap(),
+        stable_denom: "uusd".to_string(),
+        max_borrow_factor: Decimal256::percent(80),
+        borrow_grace_period: 0,
+        epoch_caller: None,
+        borrow_cooldown: 0,
+        redeem_lockup: 0,
+        stable_cw20: false,
+        tax_cap_override: None,
+        min_liquidity_ratio: Decimal256::zero(),
+        liquidation_fee_rate: Decimal256::zero(),
+        max_borrower_infos_limit: 30,
+        fallback_interest_model: None,
+        fallback_borrow_rate: None,
+        utilization_sensitivity: Decimal256::zero(),
+        reward_tenure_tiers: vec![],
+        reserve_target: None,
+        reserve_excess_recipient: None,
+        max_reserves: None,
+        max_borrow_per_tx: None,
+        guardians: vec![],
+        guardian_threshold: 0,
+        claims_paused: false,
+        rate_refresh_interval: 0,
+        min_anc_emission_rate: Decimal256::zero(),
+        low_liquidity_haircut: None,
+        max_borrow_per_block: None,
+        locked_deposit_boost: Decimal256::zero(),
+        burn_redeem_dust: false,
+        strict_supply_check: false,
+        blocks_per_year: BLOCKS_PER_YEAR,
+        max_total_deposits: None,
+        redeem_fast_path: false,
+        rounding_mode: RoundingMode::Floor,
+        partial_fill_borrows: false,
+        staking_contracts: vec![],
+        base_borrow_factor: Decimal256::percent(80),
+    };
+
+    let deposits = Decimal256::from_uint256(1000000u128);
+    let factor = |config: &Config, liabilities: u128| {
+        effective_max_borrow_factor(config, Decimal256::from_uint256(liabilities), deposits)
+    };
+
+    // zero sensitivity keeps the static factor
+    assert_eq!(factor(&mock_config, 0), Decimal256::percent(80));
+    assert_eq!(factor(&mock_config, 500000), Decimal256::percent(80));
+
+    // 80% * (1 - 50% * utilization)
+    mock_config.utilization_sensitivity = Decimal256::percent(50);
+    assert_eq!(factor(&mock_config, 0), Decimal256::percent(80));
+    assert_eq!(factor(&mock_config, 200000), Decimal256::percent(72));
+    assert_eq!(factor(&mock_config, 500000), Decimal256::percent(60));
+    assert_eq!(factor(&mock_config, 800000), Decimal256::percent(48));
+
+    // the factor bottoms out at zero
+    mock_config.utilization_sensitivity = Decimal256::percent(200);
+    assert_eq!(factor(&mock_config, 500000), Decimal256::zero());
+    assert_eq!(factor(&mock_config, 800000), Decimal256::zero());
+
+    // 100% * (1 - 50% * utilization), capped at the 80% max
+    mock_config.base_borrow_factor = Decimal256::one();
+    mock_config.utilization_sensitivity = Decimal256::percent(50);
+    assert_eq!(factor(&mock_config, 200000), Decimal256::percent(80));
+    assert_eq!(factor(&mock_config, 500000), Decimal256::percent(75));
+    assert_eq!(factor(&mock_config, 800000), Decimal256::percent(60));
+
+    // zero sensitivity still keeps the static factor
+    mock_config.utilization_sensitivity = Decimal256::zero();
+    assert_eq!(factor(&mock_config, 500000), Decimal256::percent(80));
+}
+
+#[test]
+fn proper_compute_interest_rounding() {
+    // 1.3 and 1.5 units of the last decimal place
+    let below_half = Decimal256::from_str("1.3").unwrap();
+    let half = Decimal256::from_str("1.5").unwrap();
+    let ulp = Decimal256::from_str("0.000000000000000001").unwrap();
+    let two_ulp = Decimal256::from_str("0.000000000000000002").unwrap();
+
+    assert_eq!(mul_rounded(below_half, ulp, &RoundingMode::Floor), ulp);
+    assert_eq!(mul_rounded(below_half, ulp, &RoundingMode::Ceil), two_ulp);
+    assert_eq!(mul_rounded(below_half, ulp, &RoundingMode::Nearest), ulp);
+    assert_eq!(mul_rounded(half, ulp, &RoundingMode::Floor), ulp);
+    assert_eq!(mul_rounded(half, ulp, &RoundingMode::Ceil), two_ulp);
+    assert_eq!(mul_rounded(half, ulp, &RoundingMode::Nearest), two_ulp);
+
+    // exact products are left alone by every mode
+    for rounding_mode in [
+        RoundingMode::Floor,
+        RoundingMode::Ceil,
+        RoundingMode::Nearest,
+    ] {
+        assert_eq!(
+            mul_rounded(Decimal256::from_uint256(3u64), ulp, &rounding_mode),
+            Decimal256::from_str("0.000000000000000003").unwrap()
+        );
+    }
+
+    // Floor matches the plain product
+    assert_eq!(
+        mul_rounded(below_half, ulp, &RoundingMode::Floor),
+        below_half * ulp
+    );
+
+    let env = mock_env();
+    let accrue = |rounding_mode: RoundingMode| -> Decimal256 {
+        let mut state = State {
+            total_liabilities: half,
+            total_reserves: Decimal256::zero(),
+            last_interest_updated: env.block.height,
+            last_reward_updated: env.block.height,
+            global_interest_index: Decimal256::one(),
+            global_reward_index: Decimal256::zero(),
+            anc_emission_rate: Decimal256::one(),
+            prev_aterra_supply: Uint256::zero(),
+            prev_exchange_rate: Decimal256::one(),
+            total_fixed_liabilities: Decimal256::zero(),
+            fixed_interest_per_block: Decimal256::zero(),
+            protocol_owned_aterra: Uint256::zero(),
+            pending_reserve_transfer: Uint256::zero(),
+            cached_borrow_rate: None,
+            borrow_rate_cached_at: 0,
+            peak_utilization: Decimal256::zero(),
+            borrowed_this_block: Uint256::zero(),
+            borrowed_at_block: 0,
+            cumulative_reserves_collected: Uint256::zero(),
+            stable_cw20_balance: Uint256::zero(),
+            borrower_count: 0,
+        };
+        compute_interest_raw(
+            &mut state,
+            env.block.height + 1,
+            Uint256::from(1000000u64),
+            Uint256::from(1000000u64),
+            ulp,
+            Decimal256::one(),
+            &rounding_mode,
+        );
+        state.total_liabilities - half
+    };
+
+    // a fractional accrual of 1.5 units of the last decimal place
+    assert_eq!(accrue(RoundingMode::Floor), ulp);
+    assert_eq!(accrue(RoundingMode::Ceil), two_ulp);
+    assert_eq!(accrue(RoundingMode::Nearest), two_ulp);
+
+    // settled loans are rounded to whole units the same way
+    let settle = |loan_amount: u64, global_interest_index: u64, rounding_mode: RoundingMode| {
+        let state = State {
+            total_liabilities: Decimal256::zero(),
+            total_reserves: Decimal256::zero(),
+            last_interest_updated: env.block.height,
+            last_reward_updated: env.block.height,
+            global_interest_index: Decimal256::from_uint256(global_interest_index),
+            global_reward_index: Decimal256::zero(),
+            anc_emission_rate: Decimal256::one(),
+            prev_aterra_supply: Uint256::zero(),
+            prev_exchange_rate: Decimal256::one(),
+            total_fixed_liabilities: Decimal256::zero(),
+            fixed_interest_per_block: Decimal256::zero(),
+            protocol_owned_aterra: Uint256::zero(),
+            pending_reserve_transfer: Uint256::zero(),
+            cached_borrow_rate: None,
+            borrow_rate_cached_at: 0,
+            peak_utilization: Decimal256::zero(),
+            borrowed_this_block: Uint256::zero(),
+            borrowed_at_block: 0,
+            cumulative_reserves_collected: Uint256::zero(),
+            stable_cw20_balance: Uint256::zero(),
+            borrower_count: 0,
+        };
+        let mut liability = BorrowerInfo {
+            interest_index: Decimal256::from_uint256(2u64),
+            reward_index: Decimal256::zero(),
+            loan_amount: Uint256::from(loan_amount),
+            pending_rewards: Decimal256::zero(),
+            interest_free_until: 0,
+            last_interest_updated: 0,
+            fixed_rate: None,
+            last_borrowed: 0,
+            opened_at: 0,
+            reward_updated_at: 0,
+        };
+        compute_borrower_interest(&state, &mut liability, &rounding_mode);
+        liability.loan_amount
+    };
+
+    // 4.5 units
+    assert_eq!(settle(3, 3, RoundingMode::Floor), Uint256::from(4u64));
+    assert_eq!(settle(3, 3, RoundingMode::Ceil), Uint256::from(5u64));
+    assert_eq!(settle(3, 3, RoundingMode::Nearest), Uint256::from(5u64));
+
+    // 11.5 units
+    assert_eq!(settle(23, 1, RoundingMode::Floor), Uint256::from(11u64));
+    assert_eq!(settle(23, 1, RoundingMode::Ceil), Uint256::from(12u64));
+    assert_eq!(settle(23, 1, RoundingMode::Nearest), Uint256::from(12u64));
+
+    // exact settlements are left alone by every mode
+    for rounding_mode in [
+        RoundingMode::Floor,
+        RoundingMode::Ceil,
+        RoundingMode::Nearest,
+    ] {
+        assert_eq!(settle(4, 3, rounding_mode), Uint256::from(6u64));
+    }
+}
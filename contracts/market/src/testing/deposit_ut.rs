@@ -1,9 +1,11 @@
-use crate::deposit::compute_exchange_rate;
+use crate::rates::BLOCKS_PER_YEAR;
+use crate::snapshot::MarketSnapshot;
 use crate::state::{Config, State};
 use crate::testing::mock_querier::mock_dependencies;
 use cosmwasm_bignumber::{Decimal256, Uint256};
 use cosmwasm_std::testing::{mock_env, MOCK_CONTRACT_ADDR};
 use cosmwasm_std::{Api, Coin, Uint128};
+use moneymarket::market::RoundingMode;
 
 #[test]
 fn proper_compute_exchange_rate() {
@@ -24,6 +26,40 @@ fn proper_compute_exchange_rate() {
         overseer_contract: deps.api.addr_canonicalize("overseer").unwrap(),
         stable_denom: "uusd".to_string(),
         max_borrow_factor: Decimal256::one(),
+        borrow_grace_period: 0,
+        epoch_caller: None,
+        borrow_cooldown: 0,
+        redeem_lockup: 0,
+        stable_cw20: false,
+        tax_cap_override: None,
+        min_liquidity_ratio: Decimal256::zero(),
+        liquidation_fee_rate: Decimal256::zero(),
+        max_borrower_infos_limit: 30,
+        fallback_interest_model: None,
+        fallback_borrow_rate: None,
+        utilization_sensitivity: Decimal256::zero(),
+        reward_tenure_tiers: vec![],
+        reserve_target: None,
+        reserve_excess_recipient: None,
+        max_reserves: None,
+        max_borrow_per_tx: None,
+        guardians: vec![],
+        guardian_threshold: 0,
+        claims_paused: false,
+        rate_refresh_interval: 0,
+        min_anc_emission_rate: Decimal256::zero(),
+        low_liquidity_haircut: None,
+        max_borrow_per_block: None,
+        locked_deposit_boost: Decimal256::zero(),
+        burn_redeem_dust: false,
+        strict_supply_check: false,
+        blocks_per_year: BLOCKS_PER_YEAR,
+        max_total_deposits: None,
+        redeem_fast_path: false,
+        rounding_mode: RoundingMode::Floor,
+        partial_fill_borrows: false,
+        staking_contracts: vec![],
+        base_borrow_factor: Decimal256::one(),
     };
     deps.querier.with_token_balances(&[(
         &"AT-uusd".to_string(),
@@ -40,15 +76,23 @@ fn proper_compute_exchange_rate() {
         anc_emission_rate: Decimal256::one(),
         prev_aterra_supply: Uint256::zero(),
         prev_exchange_rate: Decimal256::one(),
+        total_fixed_liabilities: Decimal256::zero(),
+        fixed_interest_per_block: Decimal256::zero(),
+        protocol_owned_aterra: Uint256::zero(),
+        pending_reserve_transfer: Uint256::zero(),
+        cached_borrow_rate: None,
+        borrow_rate_cached_at: 0,
+        peak_utilization: Decimal256::zero(),
+        borrowed_this_block: Uint256::zero(),
+        borrowed_at_block: 0,
+        cumulative_reserves_collected: Uint256::zero(),
+        stable_cw20_balance: Uint256::zero(),
+        borrower_count: 0,
     };
     let mock_deposit_amount = Some(Uint256::from(1000000u128));
 
-    let exchange_rate = compute_exchange_rate(
-        deps.as_ref(),
-        &mock_config,
-        &mock_state,
-        mock_deposit_amount,
-    )
-    .unwrap();
+    let exchange_rate = MarketSnapshot::load(deps.as_ref(), &mock_config, mock_deposit_amount)
+        .unwrap()
+        .exchange_rate(&mock_state);
     assert_eq!(exchange_rate, Decimal256::percent(50));
 }
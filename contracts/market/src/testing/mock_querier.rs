@@ -8,11 +8,14 @@ use cosmwasm_std::{
     OwnedDeps, Querier, QuerierResult, QueryRequest, SystemError, SystemResult, Uint128, WasmQuery,
 };
 use cosmwasm_storage::to_length_prefixed;
+use std::cell::Cell;
 use std::collections::HashMap;
 
-use cw20::TokenInfoResponse;
+use anchor_token::distributor::ConfigResponse as DistributorConfigResponse;
+use cw20::{BalanceResponse, TokenInfoResponse};
 use moneymarket::distribution_model::AncEmissionRateResponse;
 use moneymarket::interest_model::BorrowRateResponse;
+use moneymarket::oracle::PriceResponse;
 use moneymarket::overseer::{BorrowLimitResponse, ConfigResponse};
 use terra_cosmwasm::{TaxCapResponse, TaxRateResponse, TerraQuery, TerraQueryWrapper, TerraRoute};
 
@@ -41,6 +44,10 @@ pub enum QueryMsg {
     Config {},
     /// Query cw20 Token Info
     TokenInfo {},
+    /// Query cw20 Token Balance
+    Balance { address: String },
+    /// Query oracle price to oracle contract
+    Price { base: String, quote: String },
 }
 
 /// mock_dependencies is a drop-in replacement for cosmwasm_std::testing::mock_dependencies
@@ -64,6 +71,9 @@ pub struct WasmMockQuerier {
     tax_querier: TaxQuerier,
     borrow_rate_querier: BorrowRateQuerier,
     borrow_limit_querier: BorrowLimitQuerier,
+    oracle_price_querier: OraclePriceQuerier,
+    distributor_querier: DistributorQuerier,
+    query_count: Cell<u64>,
 }
 
 #[derive(Clone, Default)]
@@ -167,8 +177,54 @@ pub(crate) fn borrow_limit_to_map(
     borrow_limit_map
 }
 
+#[derive(Clone, Default)]
+pub struct OraclePriceQuerier {
+    oracle_price: HashMap<(String, String), (Decimal256, u64, u64)>,
+}
+
+#[allow(clippy::type_complexity)]
+impl OraclePriceQuerier {
+    pub fn new(oracle_price: &[(&(String, String), &(Decimal256, u64, u64))]) -> Self {
+        OraclePriceQuerier {
+            oracle_price: oracle_price_to_map(oracle_price),
+        }
+    }
+}
+
+#[allow(clippy::type_complexity)]
+pub(crate) fn oracle_price_to_map(
+    oracle_price: &[(&(String, String), &(Decimal256, u64, u64))],
+) -> HashMap<(String, String), (Decimal256, u64, u64)> {
+    let mut oracle_price_map: HashMap<(String, String), (Decimal256, u64, u64)> = HashMap::new();
+    for (base_quote, oracle_price) in oracle_price.iter() {
+        oracle_price_map.insert((*base_quote).clone(), **oracle_price);
+    }
+
+    oracle_price_map
+}
+
+#[derive(Clone, Default)]
+pub struct DistributorQuerier {
+    // distributor contract => (anchor token, spend limit)
+    distributor_config: HashMap<String, (String, Uint128)>,
+}
+
+impl DistributorQuerier {
+    pub fn new(distributor_config: &[(&String, &(String, Uint128))]) -> Self {
+        let mut distributor_config_map: HashMap<String, (String, Uint128)> = HashMap::new();
+        for (distributor_contract, config) in distributor_config.iter() {
+            distributor_config_map.insert((*distributor_contract).clone(), (*config).clone());
+        }
+
+        DistributorQuerier {
+            distributor_config: distributor_config_map,
+        }
+    }
+}
+
 impl Querier for WasmMockQuerier {
     fn raw_query(&self, bin_request: &[u8]) -> QuerierResult {
+        self.query_count.set(self.query_count.get() + 1);
         // MockQuerier doesn't support Custom, so we ignore it completely here
         let request: QueryRequest<TerraQueryWrapper> = match from_slice(bin_request) {
             Ok(v) => v,
@@ -254,9 +310,24 @@ impl WasmMockQuerier {
                         },
                     ))),
                     QueryMsg::Config {} => {
+                        if let Some((anchor_token, spend_limit)) = self
+                            .distributor_querier
+                            .distributor_config
+                            .get(contract_addr)
+                        {
+                            return SystemResult::Ok(ContractResult::from(to_binary(
+                                &DistributorConfigResponse {
+                                    gov_contract: "gov".to_string(),
+                                    anchor_token: anchor_token.clone(),
+                                    whitelist: vec![MOCK_CONTRACT_ADDR.to_string()],
+                                    spend_limit: *spend_limit,
+                                },
+                            )));
+                        }
+
                         SystemResult::Ok(ContractResult::from(to_binary(&ConfigResponse {
                             owner_addr: "".to_string(),
-                            oracle_contract: "".to_string(),
+                            oracle_contract: "oracle".to_string(),
                             market_contract: "".to_string(),
                             liquidation_contract: "".to_string(),
                             collector_contract: "".to_string(),
@@ -300,6 +371,32 @@ impl WasmMockQuerier {
                             total_supply,
                         })))
                     }
+                    QueryMsg::Balance { address } => {
+                        let balance: Uint128 = match self.token_querier.balances.get(contract_addr)
+                        {
+                            Some(balances) => balances.get(&address).copied().unwrap_or_default(),
+                            None => Uint128::zero(),
+                        };
+
+                        SystemResult::Ok(ContractResult::from(to_binary(&BalanceResponse {
+                            balance,
+                        })))
+                    }
+                    QueryMsg::Price { base, quote } => {
+                        match self.oracle_price_querier.oracle_price.get(&(base, quote)) {
+                            Some(v) => {
+                                SystemResult::Ok(ContractResult::from(to_binary(&PriceResponse {
+                                    rate: v.0,
+                                    last_updated_base: v.1,
+                                    last_updated_quote: v.2,
+                                })))
+                            }
+                            None => SystemResult::Err(SystemError::InvalidRequest {
+                                error: "No oracle price exists".to_string(),
+                                request: msg.as_slice().into(),
+                            }),
+                        }
+                    }
                 }
             }
             QueryRequest::Wasm(WasmQuery::Raw { contract_addr, key }) => {
@@ -353,9 +450,17 @@ impl WasmMockQuerier {
             tax_querier: TaxQuerier::default(),
             borrow_rate_querier: BorrowRateQuerier::default(),
             borrow_limit_querier: BorrowLimitQuerier::default(),
+            oracle_price_querier: OraclePriceQuerier::default(),
+            distributor_querier: DistributorQuerier::default(),
+            query_count: Cell::new(0),
         }
     }
 
+    /// # of queries made since the querier was created
+    pub fn query_count(&self) -> u64 {
+        self.query_count.get()
+    }
+
     // set a new balance for the given address and return the old balance
     pub fn update_balance<U: Into<String>>(
         &mut self,
@@ -382,4 +487,19 @@ impl WasmMockQuerier {
     pub fn with_borrow_limit(&mut self, borrow_limit: &[(&String, &Uint256)]) {
         self.borrow_limit_querier = BorrowLimitQuerier::new(borrow_limit);
     }
+
+    #[allow(clippy::type_complexity)]
+    pub fn with_oracle_price(
+        &mut self,
+        oracle_price: &[(&(String, String), &(Decimal256, u64, u64))],
+    ) {
+        self.oracle_price_querier = OraclePriceQuerier::new(oracle_price);
+    }
+
+    pub fn with_distributor_config(
+        &mut self,
+        distributor_config: &[(&String, &(String, Uint128))],
+    ) {
+        self.distributor_querier = DistributorQuerier::new(distributor_config);
+    }
 }
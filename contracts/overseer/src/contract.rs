@@ -648,6 +648,7 @@ pub fn update_epoch_state(
         target_deposit_rate: config.target_deposit_rate,
         threshold_deposit_rate: config.threshold_deposit_rate,
         distributed_interest,
+        reserve_only: false,
     })?;
 
     // proceed with deposit rate update
@@ -243,6 +243,7 @@ impl WasmMockQuerier {
                             Some(_v) => {
                                 SystemResult::Ok(ContractResult::from(to_binary(&StateResponse {
                                     total_liabilities: Decimal256::zero(),
+                                    total_fixed_liabilities: Decimal256::zero(),
                                     total_reserves: Decimal256::zero(),
                                     last_interest_updated: 0,
                                     last_reward_updated: 0,
@@ -252,6 +253,8 @@ impl WasmMockQuerier {
                                     anc_emission_rate: Decimal256::zero(),
                                     prev_aterra_supply: Uint256::zero(),
                                     prev_exchange_rate: Decimal256::zero(),
+                                    peak_utilization: Decimal256::zero(),
+                                    cumulative_reserves_collected: Uint256::zero(),
                                 })))
                             }
                             None => SystemResult::Err(SystemError::InvalidRequest {
@@ -286,6 +289,7 @@ impl WasmMockQuerier {
                                 reward_index: Decimal256::zero(),
                                 loan_amount: *v,
                                 pending_rewards: Decimal256::zero(),
+                                fixed_rate: None,
                             },
                         ))),
                         None => SystemResult::Err(SystemError::InvalidRequest {
@@ -676,6 +676,7 @@ fn update_epoch_state() {
                 target_deposit_rate: Decimal256::permille(5),
                 threshold_deposit_rate: Decimal256::from_ratio(1u64, 1000000u64),
                 distributed_interest: Uint256::from(1000000u128),
+                reserve_only: false,
             })
             .unwrap(),
         }))]
@@ -709,6 +710,7 @@ fn update_epoch_state() {
                 target_deposit_rate: Decimal256::from_str("0.000001006442178229").unwrap(),
                 threshold_deposit_rate: Decimal256::from_str("0.000001006442178229").unwrap(),
                 distributed_interest: Uint256::from(1000000u128),
+                reserve_only: false,
             })
             .unwrap(),
         }))]
@@ -1373,6 +1375,7 @@ fn dynamic_rate_model() {
                 target_deposit_rate: Decimal256::permille(5),
                 threshold_deposit_rate: Decimal256::from_ratio(1u64, 1000000u64),
                 distributed_interest: Uint256::from(1000000u128),
+                reserve_only: false,
             })
             .unwrap(),
         }))]
@@ -1407,6 +1410,7 @@ fn dynamic_rate_model() {
                 target_deposit_rate: Decimal256::from_str("0.000001001073696371").unwrap(),
                 threshold_deposit_rate: Decimal256::from_str("0.000001001073696371").unwrap(),
                 distributed_interest: Uint256::from(1000000u128),
+                reserve_only: false,
             })
             .unwrap(),
         }))]
@@ -1,7 +1,9 @@
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
+use std::fmt;
 
 use cosmwasm_bignumber::{Decimal256, Uint256};
+use cosmwasm_std::{Binary, Uint128, WasmMsg};
 use cw20::Cw20ReceiveMsg;
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -17,10 +19,31 @@ pub struct InstantiateMsg {
     pub anc_emission_rate: Decimal256,
     /// Maximum allowed borrow rate over deposited stable balance
     pub max_borrow_factor: Decimal256,
+    /// Whether the stable is a cw20 token, with `stable_denom`
+    /// set to its contract address
+    #[serde(default)]
+    pub stable_cw20: bool,
+    /// Appended to the derived aterra symbol, to tell apart
+    /// markets whose stables derive the same one
+    #[serde(default)]
+    pub symbol_suffix: Option<String>,
+    /// aterra name, where `{DENOM}` stands for the stable's name;
+    /// defaults to "Anchor Terra {DENOM}", or "Anchor {DENOM}" for a cw20
+    #[serde(default)]
+    pub aterra_name: Option<String>,
+    /// Admin allowed to migrate the aterra token;
+    /// defaults to the market contract itself
+    #[serde(default)]
+    pub aterra_admin: Option<String>,
+    /// Mint the seed aterra to a dead address rather than the market,
+    /// so it can never be redeemed
+    #[serde(default)]
+    pub lock_seed: bool,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
+#[allow(clippy::large_enum_variant)]
 pub enum ExecuteMsg {
     Receive(Cw20ReceiveMsg),
 
@@ -42,12 +65,91 @@ pub enum ExecuteMsg {
         distributor_contract: String,
     },
 
-    /// Update config values
+    /// Register a single contract address, if not registered yet
+    RegisterContract {
+        which: ContractKind,
+        address: String,
+    },
+
+    /// Update config values; the sensitive ones
+    /// go through QueueConfigChange instead
     UpdateConfig {
-        owner_addr: Option<String>,
-        max_borrow_factor: Option<Decimal256>,
-        interest_model: Option<String>,
         distribution_model: Option<String>,
+        borrow_grace_period: Option<u64>,
+        epoch_caller: Option<String>,
+        borrow_cooldown: Option<u64>,
+        redeem_lockup: Option<u64>,
+        tax_cap_override: Option<Uint128>,
+        min_liquidity_ratio: Option<Decimal256>,
+        liquidation_fee_rate: Option<Decimal256>,
+        max_borrower_infos_limit: Option<u32>,
+        fallback_interest_model: Option<String>,
+        fallback_borrow_rate: Option<Decimal256>,
+        utilization_sensitivity: Option<Decimal256>,
+        reward_tenure_tiers: Option<Vec<RewardTenureTier>>,
+        reserve_target: Option<Uint256>,
+        reserve_excess_recipient: Option<String>,
+        max_reserves: Option<Uint256>,
+        max_borrow_per_tx: Option<Uint256>,
+        guardians: Option<Vec<String>>,
+        guardian_threshold: Option<u32>,
+        claims_paused: Option<bool>,
+        rate_refresh_interval: Option<u64>,
+        min_anc_emission_rate: Option<Decimal256>,
+        low_liquidity_haircut: Option<Decimal256>,
+        max_borrow_per_block: Option<Uint256>,
+        locked_deposit_boost: Option<Decimal256>,
+        burn_redeem_dust: Option<bool>,
+        strict_supply_check: Option<bool>,
+        blocks_per_year: Option<u64>,
+        max_total_deposits: Option<Uint256>,
+        redeem_fast_path: Option<bool>,
+        rounding_mode: Option<RoundingMode>,
+        partial_fill_borrows: Option<bool>,
+        staking_contracts: Option<Vec<String>>,
+        base_borrow_factor: Option<Decimal256>,
+    },
+
+    /// Queue a timelocked change of the sensitive config values,
+    /// applicable from block height `eta`
+    QueueConfigChange {
+        change: ConfigChange,
+        eta: u64,
+    },
+
+    /// Apply the queued config changes whose eta has passed
+    ApplyConfigChange {},
+
+    /// Send a native token accidentally sent to the contract
+    /// to the recipient; the stable denom cannot be swept
+    SweepToken {
+        denom: String,
+        recipient: String,
+    },
+
+    /// Send a cw20 token accidentally sent to the contract
+    /// to the recipient; aterra cannot be swept
+    SweepCw20Token {
+        contract_addr: String,
+        recipient: String,
+    },
+
+    /// Move last_interest_updated back to recover from
+    /// a height that was set in the future. Named after the field it sets
+    /// rather than SetInterestTimestamp { time }: the market tracks
+    /// accrual by block height, not by time
+    SetLastInterestUpdated {
+        block_height: u64,
+    },
+
+    /// Restart tracking the peak utilization from zero
+    ResetPeakUtilization {},
+
+    /// Settle a page of borrowers up to the current global indices,
+    /// ordered by their canonical address
+    ResyncBorrowers {
+        start_after: Option<String>,
+        limit: Option<u32>,
     },
 
     ////////////////////
@@ -67,27 +169,199 @@ pub enum ExecuteMsg {
         target_deposit_rate: Decimal256,
         threshold_deposit_rate: Decimal256,
         distributed_interest: Uint256,
+        /// Skip the anc_emission_rate update, e.g. while
+        /// the distribution model is under maintenance
+        #[serde(default)]
+        reserve_only: bool,
     },
 
     ////////////////////
     /// User operations
     ////////////////////
     /// Deposit stable asset to get interest
-    DepositStable {},
+    DepositStable {
+        /// Referrer credited with the deposit volume
+        referrer: Option<String>,
+    },
+
+    /// Owner operation to deposit stable asset as
+    /// protocol-owned liquidity held by the market
+    DepositProtocolOwned {},
+
+    /// Deposit stable asset and send the minted aterra on to
+    /// `staking_contract` with `msg`, like a cw20 Send from the market.
+    /// The staking contract sees the market as the sender, so `msg` must
+    /// name the depositor as the beneficiary, and only staking contracts
+    /// in the config's `staking_contracts` that honor it are accepted
+    DepositAndStake {
+        staking_contract: String,
+        msg: Binary,
+    },
+
+    /// Deposit stable asset for `term_blocks`, as the market keeps time in
+    /// block height; the market holds the minted aterra, redeemable by the
+    /// depositor only after the term
+    DepositStableLocked {
+        term_blocks: u64,
+    },
+
+    /// Deposit stable asset split across `recipients`, minting each
+    /// the aterra for its share; the shares must add up to the funds sent
+    DepositStableSplit {
+        recipients: Vec<(String, Uint128)>,
+    },
+
+    /// Redeem a locked deposit whose term has ended,
+    /// with the boost on the yield it earned
+    RedeemLocked {
+        lock_id: u64,
+    },
 
     /// Borrow stable asset with collaterals in overseer contract
     BorrowStable {
         borrow_amount: Uint256,
         to: Option<String>,
+        /// Lock the current borrow rate for a newly opened position
+        #[serde(default)]
+        fixed_rate: bool,
+        /// Executed right after the borrowed stable is sent;
+        /// must target the borrow recipient
+        #[serde(default)]
+        hook: Option<WasmMsg>,
+        /// Borrow into this borrower's position as its delegate;
+        /// the stable is sent to the borrower, without a hook
+        #[serde(default)]
+        on_behalf_of: Option<String>,
     },
 
-    /// Repay stable asset to decrease liability
-    RepayStable {},
+    /// Repay stable asset to decrease liability, along with the
+    /// underlying of `aterra_amount` burnt from the sender's allowance;
+    /// aterra beyond what covers the rest of the loan is left unburnt
+    RepayStable {
+        aterra_amount: Option<Uint256>,
+    },
 
     /// Claim distributed ANC rewards
     ClaimRewards {
         to: Option<String>,
     },
+
+    /// Send the due reserves to the collector contract,
+    /// as done by epoch operations; callable by anyone
+    TransferReserves {},
+
+    /// Register a message executed on the sender
+    /// whenever its position is liquidated; None clears it
+    SetLiquidationCallback {
+        msg: Option<Binary>,
+    },
+
+    /// Let `delegate` borrow up to `limit` on behalf of the sender;
+    /// replaces any previous delegate, and None revokes it
+    SetBorrowDelegate {
+        delegate: Option<String>,
+        limit: Uint256,
+    },
+
+    ////////////////////
+    /// Guardian operations
+    ////////////////////
+    /// Propose setting `param` to `value`, a string of the
+    /// param's type; the proposer does not vote implicitly
+    ProposeParam {
+        param: ConfigParam,
+        value: String,
+    },
+
+    /// Vote on an open proposal, once per guardian
+    VoteParam {
+        proposal_id: u64,
+        approve: bool,
+    },
+
+    /// Apply a proposal that reached the guardian threshold;
+    /// callable by anyone
+    ExecuteParam {
+        proposal_id: u64,
+    },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ConfigChange {
+    pub owner_addr: Option<String>,
+    pub interest_model: Option<String>,
+    pub max_borrow_factor: Option<Decimal256>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ContractKind {
+    Overseer,
+    InterestModel,
+    DistributionModel,
+    Collector,
+    Distributor,
+}
+
+impl fmt::Display for ContractKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ContractKind::Overseer => write!(f, "overseer"),
+            ContractKind::InterestModel => write!(f, "interest_model"),
+            ContractKind::DistributionModel => write!(f, "distribution_model"),
+            ContractKind::Collector => write!(f, "collector"),
+            ContractKind::Distributor => write!(f, "distributor"),
+        }
+    }
+}
+
+/// Config values guardians can change through proposals;
+/// the timelocked ones stay with QueueConfigChange
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ConfigParam {
+    BorrowGracePeriod,
+    BorrowCooldown,
+    RedeemLockup,
+    MinLiquidityRatio,
+    LiquidationFeeRate,
+    UtilizationSensitivity,
+    ReserveTarget,
+    MaxReserves,
+    MaxBorrowPerTx,
+}
+
+impl fmt::Display for ConfigParam {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ConfigParam::BorrowGracePeriod => write!(f, "borrow_grace_period"),
+            ConfigParam::BorrowCooldown => write!(f, "borrow_cooldown"),
+            ConfigParam::RedeemLockup => write!(f, "redeem_lockup"),
+            ConfigParam::MinLiquidityRatio => write!(f, "min_liquidity_ratio"),
+            ConfigParam::LiquidationFeeRate => write!(f, "liquidation_fee_rate"),
+            ConfigParam::UtilizationSensitivity => write!(f, "utilization_sensitivity"),
+            ConfigParam::ReserveTarget => write!(f, "reserve_target"),
+            ConfigParam::MaxReserves => write!(f, "max_reserves"),
+            ConfigParam::MaxBorrowPerTx => write!(f, "max_borrow_per_tx"),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ProposalStatus {
+    Open,
+    Executed,
+    /// Too many rejections left the threshold out of reach
+    Rejected,
+}
+
+/// Reward multiplier earned by positions open for at least `min_tenure` blocks;
+/// only the top tier earns its full share of the emission
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct RewardTenureTier {
+    pub min_tenure: u64,
+    pub multiplier: Decimal256,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -96,19 +370,40 @@ pub enum Cw20HookMsg {
     /// Return stable coins to a user
     /// according to exchange rate
     RedeemStable {},
+    /// Return exactly `underlying_amount` of stable coins, burning only
+    /// the aterra it takes; the rest of the sent aterra is returned
+    RedeemUnderlying { underlying_amount: Uint256 },
+    /// Deposit a cw20 stable; only accepted from the stable token
+    DepositStable { referrer: Option<String> },
+    /// Repay a loan with a cw20 stable; only accepted from the stable token
+    RepayStable {},
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum QueryMsg {
     Config {},
+    /// Config fields set away from their instantiation defaults
+    ConfigAudit {},
+    /// State projected to `block_height`; for an earlier height the reward
+    /// index history covers, only the indices are historical, taken from
+    /// HistoricalState
     State {
         block_height: Option<u64>,
     },
+    /// Interest and reward indices at a height before the latest accrual,
+    /// interpolated from the reward index history, when it covers them;
+    /// approximate if accruals in between compounded the interest index
+    HistoricalState {
+        block_height: u64,
+    },
     EpochState {
         block_height: Option<u64>,
         distributed_interest: Option<Uint256>,
     },
+    ConfigAndState {
+        block_height: Option<u64>,
+    },
     BorrowerInfo {
         borrower: String,
         block_height: Option<u64>,
@@ -117,6 +412,136 @@ pub enum QueryMsg {
         start_after: Option<String>,
         limit: Option<u32>,
     },
+    /// Info for each listed borrower in order; unknown borrowers
+    /// are returned as zeroed entries
+    BorrowerInfosByList {
+        borrowers: Vec<String>,
+        block_height: Option<u64>,
+    },
+    ReferralVolume {
+        referrer: String,
+    },
+    BorrowerHealth {
+        borrower: String,
+    },
+    /// Whole rewards claimable now, and whether the distributor holds
+    /// the ANC and spend limit to pay them out
+    ClaimableReward {
+        borrower: String,
+    },
+    /// Whether the borrower's accrued loan exceeds its overseer borrow limit
+    IsLiquidatable {
+        borrower: String,
+    },
+    /// Share of the overseer borrow limit the borrower's accrued loan uses
+    BorrowerUtilization {
+        borrower: String,
+    },
+    /// Latest reward index snapshots taken at epoch operations, newest first
+    RewardIndexHistory {
+        limit: Option<u32>,
+    },
+    /// Latest total liabilities snapshots taken at epoch operations, newest first
+    LiabilityHistory {
+        limit: Option<u32>,
+    },
+    /// Latest reserve transfers the collector confirmed, newest first
+    ReserveTransferHistory {
+        limit: Option<u32>,
+    },
+    TotalDeposits {},
+    /// Stable left to deposit under the deposit cap, or None when uncapped
+    RemainingDepositCapacity {},
+    /// Current per block and annualized borrow and deposit rates
+    Rates {
+        block_height: Option<u64>,
+        compounding: Compounding,
+    },
+    /// Rate a borrower's loan currently accrues at
+    BorrowerRate {
+        borrower: String,
+    },
+    /// Stable that fully repays a borrower's loan at `block_height`
+    PayoffAmount {
+        borrower: String,
+        block_height: Option<u64>,
+    },
+    /// Tax deducted when the market sends `amount` of stable
+    TaxAmount {
+        amount: Uint256,
+    },
+    /// Reward state projected to `block_height`
+    RewardState {
+        block_height: Option<u64>,
+    },
+    /// Queued config changes, in queue order
+    PendingConfigChanges {},
+    /// Reserves the next epoch would send to the collector,
+    /// with interest accrued up to `block_height`
+    ProjectedReserves {
+        block_height: Option<u64>,
+    },
+    /// Per block gap between the borrow and deposit rates, kept as reserves
+    RateSpread {
+        block_height: Option<u64>,
+    },
+    /// Per block deposit rate net of the reserves' share
+    NetDepositRate {
+        block_height: Option<u64>,
+    },
+    /// Stable redeemable for `aterra_amount` at `block_height`
+    AterraToUnderlying {
+        aterra_amount: Uint256,
+        block_height: Option<u64>,
+    },
+    /// aterra minted for depositing `underlying_amount` at `block_height`
+    UnderlyingToAterra {
+        underlying_amount: Uint256,
+        block_height: Option<u64>,
+    },
+    /// Compare total liabilities with the sum of every borrower's loan;
+    /// iterates all borrowers, so only meant for off-chain checks
+    IntegrityCheck {},
+    /// Total deposits valued in `reference_denom` at the oracle price;
+    /// `oracle` defaults to the overseer's oracle
+    TvlInReference {
+        oracle: Option<String>,
+        reference_denom: String,
+    },
+    /// State, deposits, simple rates, utilization and open positions
+    /// at `block_height` in one call; counting positions iterates all
+    /// borrowers, so only meant for off-chain monitoring
+    MarketSummary {
+        block_height: Option<u64>,
+    },
+    /// Guardian parameter proposal
+    Proposal {
+        id: u64,
+    },
+    /// # of blocks since interest and rewards were last accrued
+    Freshness {},
+    LockedDeposit {
+        lock_id: u64,
+    },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Compounding {
+    Simple,
+    Daily,
+    Continuous,
+}
+
+/// How interest accrual rounds below the decimal precision
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum RoundingMode {
+    /// In favor of borrowers
+    Floor,
+    /// In favor of the protocol
+    Ceil,
+    Nearest,
 }
 
 // We define a custom struct for each query response
@@ -131,12 +556,53 @@ pub struct ConfigResponse {
     pub distributor_contract: String,
     pub stable_denom: String,
     pub max_borrow_factor: Decimal256,
+    pub borrow_grace_period: u64,
+    pub epoch_caller: Option<String>,
+    pub borrow_cooldown: u64,
+    pub redeem_lockup: u64,
+    pub stable_cw20: bool,
+    pub tax_cap_override: Option<Uint128>,
+    pub min_liquidity_ratio: Decimal256,
+    pub liquidation_fee_rate: Decimal256,
+    pub max_borrower_infos_limit: u32,
+    pub fallback_interest_model: Option<String>,
+    pub fallback_borrow_rate: Option<Decimal256>,
+    pub utilization_sensitivity: Decimal256,
+    pub reward_tenure_tiers: Vec<RewardTenureTier>,
+    pub reserve_target: Option<Uint256>,
+    pub reserve_excess_recipient: Option<String>,
+    pub max_reserves: Option<Uint256>,
+    pub max_borrow_per_tx: Option<Uint256>,
+    pub guardians: Vec<String>,
+    pub guardian_threshold: u32,
+    pub claims_paused: bool,
+    pub rate_refresh_interval: u64,
+    pub min_anc_emission_rate: Decimal256,
+    pub low_liquidity_haircut: Option<Decimal256>,
+    pub max_borrow_per_block: Option<Uint256>,
+    pub locked_deposit_boost: Decimal256,
+    pub burn_redeem_dust: bool,
+    pub strict_supply_check: bool,
+    pub blocks_per_year: u64,
+    pub max_total_deposits: Option<Uint256>,
+    pub redeem_fast_path: bool,
+    pub rounding_mode: RoundingMode,
+    pub partial_fill_borrows: bool,
+    pub staking_contracts: Vec<String>,
+    pub base_borrow_factor: Decimal256,
+}
+
+// We define a custom struct for each query response
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ConfigAuditResponse {
+    pub deviations: Vec<String>,
 }
 
 // We define a custom struct for each query response
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct StateResponse {
     pub total_liabilities: Decimal256,
+    pub total_fixed_liabilities: Decimal256,
     pub total_reserves: Decimal256,
     pub last_interest_updated: u64,
     pub last_reward_updated: u64,
@@ -145,6 +611,23 @@ pub struct StateResponse {
     pub anc_emission_rate: Decimal256,
     pub prev_aterra_supply: Uint256,
     pub prev_exchange_rate: Decimal256,
+    pub peak_utilization: Decimal256,
+    pub cumulative_reserves_collected: Uint256,
+}
+
+// We define a custom struct for each query response
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct HistoricalStateResponse {
+    pub block_height: u64,
+    pub global_interest_index: Decimal256,
+    pub global_reward_index: Decimal256,
+}
+
+// We define a custom struct for each query response
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ConfigAndStateResponse {
+    pub config: ConfigResponse,
+    pub state: StateResponse,
 }
 
 // We define a custom struct for each query response
@@ -162,6 +645,15 @@ pub struct BorrowerInfoResponse {
     pub reward_index: Decimal256,
     pub loan_amount: Uint256,
     pub pending_rewards: Decimal256,
+    pub fixed_rate: Option<Decimal256>,
+}
+
+// We define a custom struct for each query response
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ClaimableRewardResponse {
+    pub borrower: String,
+    pub pending: Uint256,
+    pub distributor_sufficient: bool,
 }
 
 // We define a custom struct for each query response
@@ -169,3 +661,238 @@ pub struct BorrowerInfoResponse {
 pub struct BorrowerInfosResponse {
     pub borrower_infos: Vec<BorrowerInfoResponse>,
 }
+
+// We define a custom struct for each query response
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct BorrowerHealthResponse {
+    pub borrower: String,
+    pub loan_amount: Uint256,
+    pub borrow_limit: Uint256,
+    /// borrow_limit / loan_amount; None without an outstanding loan
+    pub health_factor: Option<Decimal256>,
+}
+
+// We define a custom struct for each query response
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct IsLiquidatableResponse {
+    pub borrower: String,
+    pub liquidatable: bool,
+    /// Loan amount above the borrow limit
+    pub shortfall: Uint256,
+}
+
+// We define a custom struct for each query response
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct BorrowerUtilizationResponse {
+    pub borrower: String,
+    /// loan_amount / borrow_limit, clamped to one
+    pub utilization: Decimal256,
+}
+
+// We define a custom struct for each query response
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct RatesResponse {
+    pub borrow_rate: Decimal256,
+    pub deposit_rate: Decimal256,
+    pub annual_borrow_rate: Decimal256,
+    pub annual_deposit_rate: Decimal256,
+}
+
+// We define a custom struct for each query response
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct RateSpreadResponse {
+    pub borrow_rate: Decimal256,
+    pub deposit_rate: Decimal256,
+    pub spread: Decimal256,
+}
+
+// We define a custom struct for each query response
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct NetDepositRateResponse {
+    pub gross_deposit_rate: Decimal256,
+    pub reserve_factor: Decimal256,
+    pub net_deposit_rate: Decimal256,
+}
+
+// We define a custom struct for each query response
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct RewardStateResponse {
+    pub global_reward_index: Decimal256,
+    pub anc_emission_rate: Decimal256,
+    pub last_reward_updated: u64,
+}
+
+// We define a custom struct for each query response
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct IntegrityCheckResponse {
+    /// Variable and fixed-rate liabilities tracked by the state
+    pub total_liabilities: Decimal256,
+    /// Sum of every borrower's loan amount
+    pub borrower_liabilities: Decimal256,
+    /// Absolute difference of the two
+    pub discrepancy: Decimal256,
+}
+
+// We define a custom struct for each query response
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PendingConfigChangeResponse {
+    pub change: ConfigChange,
+    pub eta: u64,
+}
+
+// We define a custom struct for each query response
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PendingConfigChangesResponse {
+    pub changes: Vec<PendingConfigChangeResponse>,
+}
+
+// We define a custom struct for each query response
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ProjectedReservesResponse {
+    pub reserves: Uint256,
+}
+
+// We define a custom struct for each query response
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct TaxAmountResponse {
+    pub tax_amount: Uint256,
+}
+
+// We define a custom struct for each query response
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct AterraToUnderlyingResponse {
+    pub underlying_amount: Uint256,
+}
+
+// We define a custom struct for each query response
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct BorrowerRateResponse {
+    pub borrower: String,
+    /// per block rate
+    pub rate: Decimal256,
+    /// rate * blocks per year
+    pub annual_rate: Decimal256,
+}
+
+// We define a custom struct for each query response
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct TotalDepositsResponse {
+    pub user_deposits: Uint256,
+    pub protocol_deposits: Uint256,
+}
+
+// We define a custom struct for each query response
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct RemainingDepositCapacityResponse {
+    pub remaining: Option<Uint256>,
+}
+
+// We define a custom struct for each query response
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ReferralVolumeResponse {
+    pub referrer: String,
+    pub volume: Uint256,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct RewardIndexSnapshot {
+    pub block_height: u64,
+    pub global_reward_index: Decimal256,
+    /// Zero in snapshots taken before it was recorded
+    #[serde(default)]
+    pub global_interest_index: Decimal256,
+}
+
+// We define a custom struct for each query response
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct RewardIndexHistoryResponse {
+    pub history: Vec<RewardIndexSnapshot>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct LiabilitySnapshot {
+    pub block_height: u64,
+    pub total_liabilities: Decimal256,
+}
+
+// We define a custom struct for each query response
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct LiabilityHistoryResponse {
+    pub history: Vec<LiabilitySnapshot>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ReserveTransfer {
+    pub block_height: u64,
+    pub amount: Uint256,
+}
+
+// We define a custom struct for each query response
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ReserveTransferHistoryResponse {
+    pub history: Vec<ReserveTransfer>,
+}
+
+// We define a custom struct for each query response
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct UnderlyingToAterraResponse {
+    pub aterra_amount: Uint256,
+}
+
+// We define a custom struct for each query response
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct TvlInReferenceResponse {
+    pub total_underlying: Uint256,
+    pub price: Decimal256,
+    pub tvl: Uint256,
+}
+
+// We define a custom struct for each query response
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct MarketSummaryResponse {
+    pub state: StateResponse,
+    pub total_deposits: TotalDepositsResponse,
+    pub rates: RatesResponse,
+    pub stable_balance: Uint256,
+    pub utilization: Decimal256,
+    pub borrower_count: u64,
+}
+
+// We define a custom struct for each query response
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ProposalResponse {
+    pub id: u64,
+    pub param: ConfigParam,
+    pub value: String,
+    pub proposer: String,
+    pub approvals: u32,
+    pub rejections: u32,
+    pub status: ProposalStatus,
+}
+
+// We define a custom struct for each query response
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct FreshnessResponse {
+    pub interest_age_blocks: u64,
+    pub reward_age_blocks: u64,
+}
+
+// We define a custom struct for each query response
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct LockedDepositResponse {
+    pub lock_id: u64,
+    pub owner: String,
+    pub aterra_amount: Uint256,
+    pub principal: Uint256,
+    pub unlock_at: u64,
+}
+
+// We define a custom struct for each query response
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PayoffAmountResponse {
+    pub borrower: String,
+    /// Stable to send along with RepayStable
+    pub payoff_amount: Uint256,
+    /// Tax charged to the sender on top of `payoff_amount`
+    pub tax_amount: Uint256,
+}
@@ -6,7 +6,7 @@ use cosmwasm_std::{
     to_binary, Addr, AllBalanceResponse, BalanceResponse, BankQuery, Coin, Deps, QueryRequest,
     StdError, StdResult, Uint128, WasmQuery,
 };
-use cw20::{Cw20QueryMsg, TokenInfoResponse};
+use cw20::{BalanceResponse as Cw20BalanceResponse, Cw20QueryMsg, TokenInfoResponse};
 use terra_cosmwasm::TerraQuerier;
 
 use crate::oracle::{PriceResponse, QueryMsg as OracleQueryMsg};
@@ -30,6 +30,9 @@ pub fn query_balance(deps: Deps, account_addr: Addr, denom: String) -> StdResult
     Ok(balance.amount.amount.into())
 }
 
+/// cw20 balance of `account_addr`, read from the token's BalanceResponse.
+/// A bare Uint128 never parsed from a real token, so the balance always
+/// read as zero before; a failed query still reads as zero
 pub fn query_token_balance(
     deps: Deps,
     contract_addr: Addr,
@@ -44,6 +47,7 @@ pub fn query_token_balance(
                 address: account_addr.to_string(),
             })?,
         }))
+        .map(|res: Cw20BalanceResponse| res.balance)
         .unwrap_or_else(|_| Uint128::zero());
 
     Ok(balance.into())